@@ -0,0 +1,40 @@
+//! Compares the runtime and edge cut of KaHIP's modes.
+//!
+//! The graph is an `n` by `n` grid, where `n` is read from `KAHIP_BENCH_SIZE`
+//! and defaults to 100. Run with `cargo bench --features testgraphs`, the
+//! edge cut of each mode is printed before it is measured.
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use kahip::testgraphs::grid_2d;
+use kahip::{Mode, PartitionConfig};
+use std::env;
+
+fn modes(c: &mut Criterion) {
+    let n = env::var("KAHIP_BENCH_SIZE")
+        .ok()
+        .and_then(|n| n.parse().ok())
+        .unwrap_or(100);
+    let mut graph = grid_2d(n, n);
+
+    let mut group = c.benchmark_group(format!("partition_{n}x{n}"));
+    for mode in [Mode::Fast, Mode::Eco, Mode::Strong] {
+        let cfg = PartitionConfig::new(8).set_mode(mode);
+        let result = graph.as_graph().partition_with(&cfg);
+        println!("{mode:?}: edge cut {}", result.edge_cut);
+
+        group.bench_with_input(
+            BenchmarkId::from_parameter(format!("{mode:?}")),
+            &cfg,
+            |b, cfg| b.iter(|| graph.as_graph().partition_with(cfg)),
+        );
+    }
+    group.finish();
+}
+
+criterion_group! {
+    name = benches;
+    // KaHIP's strong mode is slow, keep the number of runs low.
+    config = Criterion::default().sample_size(10);
+    targets = modes
+}
+criterion_main!(benches);
@@ -0,0 +1,9 @@
+use std::env;
+
+fn main() {
+    // Set by kahip-sys when the installed KaHIP has `kaffpa_balance_NE`.
+    println!("cargo:rustc-check-cfg=cfg(kahip_balance_ne)");
+    if env::var_os("DEP_KAHIP_BALANCE_NE").is_some() {
+        println!("cargo:rustc-cfg=kahip_balance_ne");
+    }
+}
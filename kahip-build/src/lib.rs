@@ -0,0 +1,177 @@
+//! Search and link logic of the build script of `kahip-sys`, kept in its own
+//! crate so that it can be tested without KaHIP or libclang, which building
+//! `kahip-sys` itself requires.
+
+use std::path::{Path, PathBuf};
+
+/// Returns the directories of KaHIP's header and library.
+///
+/// The header is looked up in `include_dir`, then in the `include`
+/// directory of `kahip_dir`, then in the include directory given by
+/// `pkg_config`, and finally in `/usr/include`. The library is looked up
+/// likewise in `lib_dir`, the `lib` directory of `kahip_dir` and the library
+/// directory given by `pkg_config`, or left to the linker's default search
+/// path. `pkg_config` is only called when needed.
+pub fn search_dirs<F>(
+    kahip_dir: Option<PathBuf>,
+    include_dir: Option<PathBuf>,
+    lib_dir: Option<PathBuf>,
+    pkg_config: F,
+) -> (PathBuf, Option<PathBuf>)
+where
+    F: FnOnce() -> Option<(Option<PathBuf>, Option<PathBuf>)>,
+{
+    let needs_pkg_config = kahip_dir.is_none() && (include_dir.is_none() || lib_dir.is_none());
+    let (pkg_include_dir, pkg_lib_dir) = if needs_pkg_config {
+        pkg_config().unwrap_or_default()
+    } else {
+        (None, None)
+    };
+
+    let include_dir = include_dir
+        .or_else(|| kahip_dir.as_ref().map(|dir| dir.join("include")))
+        .or(pkg_include_dir)
+        .unwrap_or_else(|| PathBuf::from("/usr/include"));
+    let lib_dir = lib_dir
+        .or_else(|| kahip_dir.as_ref().map(|dir| dir.join("lib")))
+        .or(pkg_lib_dir);
+    (include_dir, lib_dir)
+}
+
+/// Checks that `header` exists, or returns an error message telling how to
+/// point the build script to KaHIP.
+pub fn check_header(header: &Path) -> Result<(), String> {
+    if header.is_file() {
+        return Ok(());
+    }
+    Err(format!(
+        "Couldn't find {}. Set KAHIP_DIR to the directory where KaHIP is \
+         installed, or KAHIP_INCLUDE_DIR to the directory of its headers, or \
+         enable the `vendored` feature to build KaHIP from source.",
+        header.display()
+    ))
+}
+
+/// Returns the version of KaHIP defined in its header, either by a
+/// `KAHIP_VERSION` string or by `KAHIP_VERSION_MAJOR`, `KAHIP_VERSION_MINOR`
+/// and `KAHIP_VERSION_PATCH` numbers.
+pub fn header_version(header: &str) -> Option<String> {
+    let define = |name: &str| {
+        header.lines().find_map(|line| {
+            let mut tokens = line.split_whitespace();
+            if tokens.next() != Some("#define") || tokens.next() != Some(name) {
+                return None;
+            }
+            tokens
+                .next()
+                .map(|value| value.trim_matches('"').to_string())
+        })
+    };
+    if let Some(version) = define("KAHIP_VERSION") {
+        return Some(version);
+    }
+    let major = define("KAHIP_VERSION_MAJOR")?;
+    let minor = define("KAHIP_VERSION_MINOR").unwrap_or_else(|| "0".to_string());
+    let patch = define("KAHIP_VERSION_PATCH").unwrap_or_else(|| "0".to_string());
+    Some(format!("{major}.{minor}.{patch}"))
+}
+
+/// Returns whether `header` declares 64-bit integers for KaHIP's interface,
+/// through a `typedef` or a `#define` of `int64_t` or `long long`.
+///
+/// KaHIP's own header only uses `int`, so this is how a build of KaHIP with
+/// 64-bit indices must be marked for the `idx64` feature.
+pub fn header_is_64bit(header: &str) -> bool {
+    header.lines().any(|line| {
+        let tokens: Vec<&str> = line
+            .split(|c: char| c.is_whitespace() || c == ';')
+            .filter(|token| !token.is_empty())
+            .collect();
+        let is_64bit = |types: &[&str]| {
+            types.contains(&"int64_t") || types.windows(2).any(|w| w == ["long", "long"])
+        };
+        match tokens.as_slice() {
+            ["typedef", types @ .., _name] => is_64bit(types),
+            ["#define", _name, types @ ..] => is_64bit(types),
+            _ => false,
+        }
+    })
+}
+
+/// Returns the `cargo:` directives to link against KaHIP, found in `lib_dir`
+/// if given, for the target OS given by `CARGO_CFG_TARGET_OS`.
+///
+/// The library is named `kahip` everywhere: `libkahip.so`, `libkahip.dylib`
+/// or `libkahip.a` on Unix, `kahip.lib` on Windows. Dynamically linked Unix
+/// binaries get an rpath to `lib_dir`, Windows has no such thing, see
+/// [`dll_path`].
+///
+/// When `static_link` is set, the libraries KaHIP depends on are linked too:
+/// the C++ standard library and OpenMP.
+pub fn link_directives(lib_dir: Option<&str>, target_os: &str, static_link: bool) -> Vec<String> {
+    let mut directives = Vec::new();
+    if let Some(lib_dir) = lib_dir {
+        directives.push(format!("cargo:rustc-link-search={lib_dir}"));
+        if !static_link {
+            if target_os == "macos" || target_os == "linux" {
+                directives.push(format!("cargo:rustc-link-arg=-Wl,-rpath,{lib_dir}"));
+            }
+            // non standard key
+            // see https://doc.rust-lang.org/cargo/reference/build-script-examples.html#linking-to-system-libraries
+            // and https://github.com/rust-lang/cargo/issues/5077
+            directives.push(format!("cargo:rpath={lib_dir}"));
+        }
+    }
+    if !static_link {
+        directives.push("cargo:rustc-link-lib=kahip".to_string());
+        return directives;
+    }
+    directives.push("cargo:rustc-link-lib=static=kahip".to_string());
+    // MSVC links its runtime and OpenMP through directives embedded in the
+    // static library.
+    let cxx = match target_os {
+        "macos" | "ios" | "freebsd" | "openbsd" => Some("c++"),
+        "windows" => None,
+        _ => Some("stdc++"),
+    };
+    for dependency in cxx.into_iter().chain(openmp_library(target_os)) {
+        directives.push(format!("cargo:rustc-link-lib={dependency}"));
+    }
+    directives
+}
+
+/// Returns the OpenMP runtime KaHIP is usually built with on `target_os`,
+/// if it has to be linked explicitly.
+pub fn openmp_library(target_os: &str) -> Option<&'static str> {
+    match target_os {
+        "macos" | "ios" | "freebsd" | "openbsd" => Some("omp"),
+        "windows" => None,
+        _ => Some("gomp"),
+    }
+}
+
+/// Returns the path of `kahip.dll`, looked up in the `bin` directory of
+/// `KAHIP_DIR` then in `lib_dir`, if it exists.
+pub fn dll_path(kahip_dir: Option<&Path>, lib_dir: &Path) -> Option<PathBuf> {
+    kahip_dir
+        .map(|dir| dir.join("bin"))
+        .into_iter()
+        .chain([lib_dir.to_path_buf()])
+        .map(|dir| dir.join("kahip.dll"))
+        .find(|dll| dll.is_file())
+}
+
+/// Returns the directories where the binaries of the build are written,
+/// given the `OUT_DIR` of the build script: `target/<profile>` and its
+/// `deps` and `examples` subdirectories.
+pub fn binary_dirs(out_dir: &Path) -> Vec<PathBuf> {
+    // `OUT_DIR` is `target/<profile>/build/kahip-sys-<hash>/out`.
+    match out_dir.ancestors().nth(3) {
+        Some(profile_dir) => vec![
+            profile_dir.to_path_buf(),
+            profile_dir.join("deps"),
+            profile_dir.join("examples"),
+        ],
+        None => Vec::new(),
+    }
+}
@@ -0,0 +1,176 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+#[test]
+fn search_dirs() {
+    let dir = |path: &str| Some(PathBuf::from(path));
+    let pkg_config = || Some((dir("/pc/include"), dir("/pc/lib")));
+    let no_pkg_config = || None;
+
+    assert_eq!(
+        kahip_build::search_dirs(dir("/kahip"), None, None, || unreachable!()),
+        (PathBuf::from("/kahip/include"), dir("/kahip/lib"))
+    );
+    assert_eq!(
+        kahip_build::search_dirs(dir("/kahip"), dir("/inc"), dir("/lib"), || unreachable!()),
+        (PathBuf::from("/inc"), dir("/lib"))
+    );
+    assert_eq!(
+        kahip_build::search_dirs(None, None, None, pkg_config),
+        (PathBuf::from("/pc/include"), dir("/pc/lib"))
+    );
+    assert_eq!(
+        kahip_build::search_dirs(None, dir("/inc"), None, pkg_config),
+        (PathBuf::from("/inc"), dir("/pc/lib"))
+    );
+    assert_eq!(
+        kahip_build::search_dirs(None, None, None, no_pkg_config),
+        (PathBuf::from("/usr/include"), None)
+    );
+}
+
+#[test]
+fn check_header() {
+    let header = Path::new("/nonexistent/kahip/include/kaHIP_interface.h");
+    let message = kahip_build::check_header(header).unwrap_err();
+    assert!(message.contains("/nonexistent/kahip/include/kaHIP_interface.h"));
+    assert!(message.contains("KAHIP_DIR"));
+    assert!(message.contains("KAHIP_INCLUDE_DIR"));
+
+    let dir = Path::new(env!("CARGO_TARGET_TMPDIR")).join("kahip-header");
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(dir.join("kaHIP_interface.h"), "").unwrap();
+    assert_eq!(
+        kahip_build::check_header(&dir.join("kaHIP_interface.h")),
+        Ok(())
+    );
+}
+
+#[test]
+fn header_version() {
+    assert_eq!(
+        kahip_build::header_version("#ifndef KAHIP_H\n#define KAHIP_VERSION \"3.16\"\n"),
+        Some("3.16".to_string())
+    );
+    assert_eq!(
+        kahip_build::header_version(
+            "#define KAHIP_VERSION_MAJOR 3\n#define KAHIP_VERSION_MINOR 14\n#define KAHIP_VERSION_PATCH 1\n"
+        ),
+        Some("3.14.1".to_string())
+    );
+    assert_eq!(kahip_build::header_version("const int FAST = 0;\n"), None);
+}
+
+#[test]
+fn header_is_64bit() {
+    assert!(kahip_build::header_is_64bit("typedef int64_t idxtype;\n"));
+    assert!(kahip_build::header_is_64bit("typedef long long idxtype;\n"));
+    assert!(kahip_build::header_is_64bit("#define idxtype int64_t\n"));
+    assert!(!kahip_build::header_is_64bit(
+        "void kaffpa(int* n, int* vwgt, int* xadj);\n"
+    ));
+    assert!(!kahip_build::header_is_64bit("typedef int idxtype;\n"));
+    assert!(!kahip_build::header_is_64bit(
+        "// typedef int64_t idxtype;\n"
+    ));
+}
+
+#[test]
+fn link_directives() {
+    assert_eq!(
+        kahip_build::link_directives(Some("/opt/kahip/lib"), "linux", false),
+        [
+            "cargo:rustc-link-search=/opt/kahip/lib",
+            "cargo:rustc-link-arg=-Wl,-rpath,/opt/kahip/lib",
+            "cargo:rpath=/opt/kahip/lib",
+            "cargo:rustc-link-lib=kahip",
+        ]
+    );
+    assert_eq!(
+        kahip_build::link_directives(Some(r"C:\kahip\lib"), "windows", false),
+        [
+            r"cargo:rustc-link-search=C:\kahip\lib",
+            r"cargo:rpath=C:\kahip\lib",
+            "cargo:rustc-link-lib=kahip",
+        ]
+    );
+}
+
+#[test]
+fn link_directives_static() {
+    assert_eq!(
+        kahip_build::link_directives(Some("/opt/kahip/lib"), "linux", true),
+        [
+            "cargo:rustc-link-search=/opt/kahip/lib",
+            "cargo:rustc-link-lib=static=kahip",
+            "cargo:rustc-link-lib=stdc++",
+            "cargo:rustc-link-lib=gomp",
+        ]
+    );
+    assert_eq!(
+        kahip_build::link_directives(None, "macos", true),
+        [
+            "cargo:rustc-link-lib=static=kahip",
+            "cargo:rustc-link-lib=c++",
+            "cargo:rustc-link-lib=omp",
+        ]
+    );
+    assert_eq!(
+        kahip_build::link_directives(None, "windows", true),
+        ["cargo:rustc-link-lib=static=kahip"]
+    );
+    assert_eq!(
+        kahip_build::link_directives(None, "linux", false),
+        ["cargo:rustc-link-lib=kahip"]
+    );
+}
+
+#[test]
+fn openmp_library() {
+    assert_eq!(kahip_build::openmp_library("linux"), Some("gomp"));
+    assert_eq!(kahip_build::openmp_library("macos"), Some("omp"));
+    assert_eq!(kahip_build::openmp_library("windows"), None);
+}
+
+#[test]
+fn dll_path() {
+    let kahip_dir = env!("CARGO_TARGET_TMPDIR");
+    let kahip_dir = Path::new(kahip_dir).join("kahip-windows");
+    let bin_dir = kahip_dir.join("bin");
+    let lib_dir = kahip_dir.join("lib");
+    fs::create_dir_all(&bin_dir).unwrap();
+    fs::create_dir_all(&lib_dir).unwrap();
+    let _ = fs::remove_file(bin_dir.join("kahip.dll"));
+    let _ = fs::remove_file(lib_dir.join("kahip.dll"));
+
+    assert_eq!(kahip_build::dll_path(Some(&kahip_dir), &lib_dir), None);
+
+    fs::write(lib_dir.join("kahip.dll"), "").unwrap();
+    assert_eq!(
+        kahip_build::dll_path(Some(&kahip_dir), &lib_dir),
+        Some(lib_dir.join("kahip.dll"))
+    );
+
+    fs::write(bin_dir.join("kahip.dll"), "").unwrap();
+    assert_eq!(
+        kahip_build::dll_path(Some(&kahip_dir), &lib_dir),
+        Some(bin_dir.join("kahip.dll"))
+    );
+    assert_eq!(
+        kahip_build::dll_path(None, &lib_dir),
+        Some(lib_dir.join("kahip.dll"))
+    );
+}
+
+#[test]
+fn binary_dirs() {
+    let out_dir = Path::new("target/debug/build/kahip-sys-0123/out");
+    assert_eq!(
+        kahip_build::binary_dirs(out_dir),
+        [
+            Path::new("target/debug"),
+            Path::new("target/debug/deps"),
+            Path::new("target/debug/examples"),
+        ]
+    );
+}
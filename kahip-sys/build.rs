@@ -1,42 +1,226 @@
+//! Generates the bindings to KaHIP and links against it.
+//!
+//! KaHIP is looked up in `KAHIP_DIR`, whose `include` directory holds
+//! `kaHIP_interface.h` and whose `lib` directory holds the library. They can
+//! be overridden with `KAHIP_INCLUDE_DIR` and `KAHIP_LIB_DIR`.
+//!
+//! When these variables do not locate both the header and the library,
+//! pkg-config is queried for `kahip`, then `libkahip`, to fill in the
+//! missing directories. The order of precedence is thus:
+//!
+//! 1. `KAHIP_INCLUDE_DIR` and `KAHIP_LIB_DIR`,
+//! 2. `KAHIP_DIR`,
+//! 3. pkg-config,
+//! 4. `/usr/include` for the header and the linker's default search path for
+//!    the library.
+//!
+//! When pkg-config is not installed or does not know of KaHIP, the last
+//! step is used.
+//!
+//! On Windows, `KAHIP_DIR` is laid out as follows:
+//!
+//! ```text
+//! KAHIP_DIR
+//! ├── bin
+//! │   └── kahip.dll
+//! ├── include
+//! │   └── kaHIP_interface.h
+//! └── lib
+//!     └── kahip.lib
+//! ```
+//!
+//! where `kahip.lib` is the import library of `kahip.dll`. The DLL may also
+//! be in the `lib` directory. Since Windows has no rpath, the DLL is copied
+//! next to the binaries of the build so that tests and examples can run.
+//!
+//! With the `static` feature, KaHIP is linked statically and the library
+//! directory must hold `libkahip.a`, or `kahip.lib` as a static library on
+//! Windows. KaHIP's CMake build names it `libkahip_static.a`, so it must be
+//! copied or linked to `libkahip.a`. The C++ standard library and OpenMP are
+//! linked too, and must be found by the linker: `libstdc++` and `libgomp`
+//! with GCC, `libc++` and `libomp` on macOS.
+//!
+//! The version of KaHIP is taken from the header if it defines one, or from
+//! pkg-config, or from the release built by the `vendored` feature. It is
+//! `unknown` otherwise.
+//!
+//! With the `mpi` feature, the bindings to ParHIP, KaHIP's distributed
+//! partitioner, are generated from `parhip_interface.h`, found next to
+//! `kaHIP_interface.h`, and `libparhip_interface` is linked. The directory
+//! of `mpi.h` can be given with `MPI_INCLUDE_DIR` if the compiler does not
+//! find it.
+//!
+//! With the `idx64` feature, the header must declare KaHIP's interface with
+//! 64-bit integers, through a `typedef` or a `#define` of `int64_t` or
+//! `long long`, and the bindings use them as declared. The build fails
+//! otherwise, since KaHIP's own header only uses `int`, and this feature
+//! cannot be combined with `vendored`.
+//!
+//! With the `vendored` feature, which implies `static`, KaHIP is built from
+//! source instead and the variables above are ignored. The sources are
+//! taken from `KAHIP_SRC_DIR` if set, or from the `KaHIP` submodule packaged
+//! with this crate, at the release given by [`KAHIP_VERSION`]. In a git
+//! checkout, the submodule is fetched with `git submodule update --init`.
+//! Building them requires CMake, a C++ compiler and OpenMP.
+
+use kahip_build as link;
 use std::env;
+use std::fs;
+#[cfg(feature = "vendored")]
+use std::path::Path;
 use std::path::PathBuf;
 
+// KaHIP's CMake build has no option for 64-bit integers.
+#[cfg(all(feature = "vendored", feature = "idx64"))]
+compile_error!("The `idx64` feature cannot be combined with `vendored`, which builds KaHIP with 32-bit integers.");
+
+/// Looks KaHIP up with pkg-config, through a `kahip.pc` or `libkahip.pc`
+/// file.
+#[cfg(not(feature = "vendored"))]
+fn pkg_config() -> Option<pkg_config::Library> {
+    ["kahip", "libkahip"].into_iter().find_map(|name| {
+        pkg_config::Config::new()
+            .cargo_metadata(false)
+            .env_metadata(true)
+            .probe(name)
+            .ok()
+    })
+}
+
+/// The release of KaHIP the `KaHIP` submodule is checked out at.
+#[cfg(feature = "vendored")]
+const KAHIP_VERSION: &str = "v3.16";
+
+/// Builds KaHIP with CMake and returns its include and library directories.
+#[cfg(feature = "vendored")]
+fn build_vendored() -> (PathBuf, PathBuf) {
+    println!("cargo:rerun-if-env-changed=KAHIP_SRC_DIR");
+    let src_dir = match env::var_os("KAHIP_SRC_DIR") {
+        Some(x) => PathBuf::from(x),
+        None => Path::new(env!("CARGO_MANIFEST_DIR")).join("KaHIP"),
+    };
+    assert!(
+        src_dir.join("CMakeLists.txt").is_file(),
+        "Couldn't find KaHIP's sources in {}. Run `git submodule update --init` \
+         or set KAHIP_SRC_DIR to a checkout of KaHIP {}.",
+        src_dir.display(),
+        KAHIP_VERSION
+    );
+
+    let dst = cmake::Config::new(&src_dir)
+        .define("CMAKE_POSITION_INDEPENDENT_CODE", "ON")
+        .define("NOMPI", if cfg!(feature = "mpi") { "OFF" } else { "ON" })
+        .build();
+
+    // KaHIP names its static library `kahip_static`, link it as `kahip`.
+    let lib_dir = dst.join("lib");
+    for (from, to) in [
+        ("libkahip_static.a", "libkahip.a"),
+        ("kahip_static.lib", "kahip.lib"),
+    ] {
+        if lib_dir.join(from).is_file() {
+            fs::copy(lib_dir.join(from), lib_dir.join(to)).expect("Couldn't copy KaHIP");
+        }
+    }
+    (dst.join("include"), lib_dir)
+}
+
 fn main() {
-    let include_dir = match env::var("KAHIP_INCLUDE_DIR") {
-        Ok(x) => PathBuf::from(x),
-        Err(_) => match env::var("KAHIP_DIR") {
-            Ok(x) => PathBuf::from(x).join("include"),
-            Err(_) => PathBuf::from("/usr/include"),
-        },
+    for var in ["KAHIP_DIR", "KAHIP_INCLUDE_DIR", "KAHIP_LIB_DIR"] {
+        println!("cargo:rerun-if-env-changed={}", var);
+    }
+    let out_dir = PathBuf::from(env::var_os("OUT_DIR").unwrap());
+    #[cfg(feature = "vendored")]
+    let (include_dir, lib_dir, version) = {
+        let (include_dir, lib_dir) = build_vendored();
+        let version = KAHIP_VERSION.trim_start_matches('v').to_string();
+        (include_dir, Some(lib_dir), Some(version))
     };
-    let kahip_h = include_dir
-        .join("kaHIP_interface.h")
+    #[cfg(not(feature = "vendored"))]
+    let (include_dir, lib_dir, version) = {
+        let mut version = None;
+        let (include_dir, lib_dir) = link::search_dirs(
+            env::var_os("KAHIP_DIR").map(PathBuf::from),
+            env::var_os("KAHIP_INCLUDE_DIR").map(PathBuf::from),
+            env::var_os("KAHIP_LIB_DIR").map(PathBuf::from),
+            || {
+                let library = pkg_config()?;
+                version = Some(library.version);
+                Some((
+                    library.include_paths.into_iter().next(),
+                    library.link_paths.into_iter().next(),
+                ))
+            },
+        );
+        (include_dir, lib_dir, version)
+    };
+    let kahip_h = include_dir.join("kaHIP_interface.h");
+    if let Err(message) = link::check_header(&kahip_h) {
+        panic!("{}", message);
+    }
+    let header = fs::read_to_string(&kahip_h).ok();
+    // A version defined by the header itself is the most reliable.
+    let version = header.as_deref().and_then(link::header_version).or(version);
+    println!(
+        "cargo:rustc-env=KAHIP_VERSION={}",
+        version.as_deref().unwrap_or("unknown")
+    );
+    // Older releases lack `kaffpa_balance_NE`, tell the `kahip` crate, which
+    // reads it as `DEP_KAHIP_BALANCE_NE`, whether the bindings have it.
+    if header
+        .as_deref()
+        .is_some_and(|header| header.contains("kaffpa_balance_NE"))
+    {
+        println!("cargo:balance_ne=1");
+    }
+    let kahip_h = kahip_h
         .into_os_string()
         .into_string()
         .expect("Not an UTF-8 path");
-    let lib_dir = match env::var("KAHIP_LIB_DIR") {
-        Ok(x) => Some(PathBuf::from(x)),
-        Err(_) => env::var("KAHIP_DIR")
-            .map(|x| PathBuf::from(x).join("lib"))
-            .ok(),
-    };
-    if let Some(lib_dir) = lib_dir {
-        let lib_dir = lib_dir
-            .into_os_string()
-            .into_string()
-            .expect("Not an UTF-8 path");
-        println!("cargo:rustc-link-search={}", lib_dir);
-        #[cfg(target_os = "macos")]
-        println!("cargo:rustc-link-arg=-Wl,-rpath,{}", lib_dir);
-        #[cfg(target_os = "linux")]
-        println!("cargo:rustc-link-arg=-Wl,-rpath,{}", lib_dir);
-        // non standard key
-        // see https://doc.rust-lang.org/cargo/reference/build-script-examples.html#linking-to-system-libraries
-        // and https://github.com/rust-lang/cargo/issues/5077
-        println!("cargo:rpath={}", lib_dir);
+    let target_os = env::var("CARGO_CFG_TARGET_OS").unwrap_or_default();
+    let static_link = env::var_os("CARGO_FEATURE_STATIC").is_some();
+    let lib_dir_str = lib_dir
+        .as_deref()
+        .map(|lib_dir| lib_dir.to_str().expect("Not an UTF-8 path"));
+    for directive in link::link_directives(lib_dir_str, &target_os, static_link) {
+        println!("{}", directive);
+    }
+    // The `openmp` feature calls into OpenMP directly, which must then be
+    // linked even when only the shared KaHIP library depends on it.
+    if env::var_os("CARGO_FEATURE_OPENMP").is_some() && !static_link {
+        if let Some(openmp) = link::openmp_library(&target_os) {
+            println!("cargo:rustc-link-lib={openmp}");
+        }
+    }
+    if target_os == "windows" && !static_link {
+        // Windows binaries look for DLLs next to them, copy it there.
+        let kahip_dir = env::var_os("KAHIP_DIR").map(PathBuf::from);
+        let dll = lib_dir
+            .as_deref()
+            .and_then(|lib_dir| link::dll_path(kahip_dir.as_deref(), lib_dir));
+        if let Some(dll) = dll {
+            println!("cargo:rerun-if-changed={}", dll.display());
+            for dir in link::binary_dirs(&out_dir) {
+                // Best effort, the DLL may also be found through `PATH`.
+                let _ = fs::create_dir_all(&dir);
+                let _ = fs::copy(&dll, dir.join("kahip.dll"));
+            }
+        }
     }
     println!("cargo:rerun-if-changed={}", kahip_h);
-    println!("cargo:rustc-link-lib=kahip");
+
+    // The bindings follow the header, so a 64-bit build of KaHIP must say so
+    // there: a header with plain `int`s would have `i64` buffers read as
+    // `int`s.
+    if env::var_os("CARGO_FEATURE_IDX64").is_some()
+        && !header.as_deref().is_some_and(link::header_is_64bit)
+    {
+        panic!(
+            "The `idx64` feature needs a KaHIP built with 64-bit integers, but \
+             {} declares none. Disable `idx64` to use this KaHIP.",
+            kahip_h
+        );
+    }
 
     bindgen::Builder::default()
         .header("stdbool.h")
@@ -52,6 +236,42 @@ fn main() {
         .allowlist_var("MAPMODE_.*")
         .generate()
         .expect("Unable to generate bindings")
-        .write_to_file("src/binding.rs")
+        .write_to_file(out_dir.join("binding.rs"))
         .expect("Couldn't write bindings!");
+
+    if env::var_os("CARGO_FEATURE_MPI").is_some() {
+        let parhip_h = include_dir.join("parhip_interface.h");
+        if let Err(message) = link::check_header(&parhip_h) {
+            panic!("{}", message);
+        }
+        let parhip_h = parhip_h
+            .into_os_string()
+            .into_string()
+            .expect("Not an UTF-8 path");
+        println!("cargo:rerun-if-changed={}", parhip_h);
+        println!("cargo:rerun-if-env-changed=MPI_INCLUDE_DIR");
+        if static_link {
+            println!("cargo:rustc-link-lib=static=parhip_interface");
+        } else {
+            println!("cargo:rustc-link-lib=parhip_interface");
+        }
+
+        let mut builder = bindgen::Builder::default();
+        if let Ok(mpi_include_dir) = env::var("MPI_INCLUDE_DIR") {
+            builder = builder.clang_arg(format!("-I{}", mpi_include_dir));
+        }
+        // ParHIP's header defines modes with the same names as KaHIP's but
+        // other values, hence the separate module. Communicators are only
+        // passed through, their type depends on the MPI implementation.
+        builder
+            .header("stdbool.h")
+            .header(parhip_h)
+            .allowlist_function("ParHIPPartitionKWay")
+            .blocklist_type("MPI_Comm")
+            .raw_line("pub type MPI_Comm = ::core::ffi::c_void;")
+            .generate()
+            .expect("Unable to generate ParHIP bindings")
+            .write_to_file(out_dir.join("parhip_binding.rs"))
+            .expect("Couldn't write ParHIP bindings!");
+    }
 }
@@ -1,2 +1,22 @@
-mod binding;
+mod binding {
+    // Generated by the build script from the installed KaHIP headers.
+    include!(concat!(env!("OUT_DIR"), "/binding.rs"));
+}
 pub use binding::*;
+
+#[cfg(feature = "mpi")]
+pub mod parhip;
+
+#[cfg(feature = "openmp")]
+extern "C" {
+    /// Sets the number of threads of the next parallel regions started by
+    /// the calling thread.
+    pub fn omp_set_num_threads(num_threads: std::os::raw::c_int);
+
+    /// Returns the number of threads of the next parallel regions started
+    /// by the calling thread.
+    pub fn omp_get_max_threads() -> std::os::raw::c_int;
+}
+
+/// The version of KaHIP the bindings were generated for, or `unknown`.
+pub const VERSION: &str = env!("KAHIP_VERSION");
@@ -0,0 +1,7 @@
+//! Bindings to ParHIP, KaHIP's distributed partitioner.
+
+mod binding {
+    // Generated by the build script from the installed ParHIP header.
+    include!(concat!(env!("OUT_DIR"), "/parhip_binding.rs"));
+}
+pub use binding::*;
@@ -0,0 +1,22 @@
+//! Checks that the generated bindings are reachable from the crate root.
+
+#[test]
+fn functions() {
+    let _ = kahip_sys::kaffpa as *const ();
+    let _ = kahip_sys::node_separator as *const ();
+    let _ = kahip_sys::reduced_nd as *const ();
+    let _ = kahip_sys::edge_partitioning as *const ();
+    let _ = kahip_sys::process_mapping as *const ();
+}
+
+#[test]
+fn constants() {
+    assert_eq!(kahip_sys::FAST, 0);
+    assert_eq!(kahip_sys::ECO, 1);
+    assert_eq!(kahip_sys::STRONG, 2);
+    assert_eq!(kahip_sys::FASTSOCIAL, 3);
+    assert_eq!(kahip_sys::ECOSOCIAL, 4);
+    assert_eq!(kahip_sys::STRONGSOCIAL, 5);
+    assert_eq!(kahip_sys::MAPMODE_MULTISECTION, 0);
+    assert_eq!(kahip_sys::MAPMODE_BISECTION, 1);
+}
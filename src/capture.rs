@@ -0,0 +1,164 @@
+//! Capture of what C code prints to the standard output and error.
+
+use std::fs::File;
+use std::io::{self, Read};
+use std::os::raw::{c_int, c_void};
+use std::os::unix::io::{AsRawFd, FromRawFd, OwnedFd};
+use std::ptr;
+use std::sync::Mutex;
+use std::thread;
+
+extern "C" {
+    fn dup(fd: c_int) -> c_int;
+    fn dup2(fd: c_int, fd2: c_int) -> c_int;
+    fn pipe(fds: *mut c_int) -> c_int;
+    fn fflush(stream: *mut c_void) -> c_int;
+}
+
+const STDOUT_FILENO: c_int = 1;
+const STDERR_FILENO: c_int = 2;
+
+/// Serializes the captures, which would otherwise swap each other's file
+/// descriptors.
+static LOCK: Mutex<()> = Mutex::new(());
+
+/// Duplicates `fd`, closing the copy when dropped.
+fn dup_owned(fd: c_int) -> io::Result<OwnedFd> {
+    let copy = unsafe { dup(fd) };
+    if copy < 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(unsafe { OwnedFd::from_raw_fd(copy) })
+}
+
+/// Makes `fd2` refer to the same file as `fd`.
+fn redirect(fd: c_int, fd2: c_int) -> io::Result<()> {
+    if unsafe { dup2(fd, fd2) } < 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(())
+}
+
+/// Restores the standard output and error when dropped, which also closes
+/// the pipe and ends the drain thread.
+///
+/// It is dropped when `f` panics as well, so that the process does not keep
+/// printing into a pipe that nothing reads.
+struct Restore {
+    stdout: OwnedFd,
+    stderr: OwnedFd,
+}
+
+impl Drop for Restore {
+    fn drop(&mut self) {
+        unsafe { fflush(ptr::null_mut()) };
+        // There is nothing better to do on failure, and panicking while
+        // unwinding would abort.
+        let _ = redirect(self.stdout.as_raw_fd(), STDOUT_FILENO);
+        let _ = redirect(self.stderr.as_raw_fd(), STDERR_FILENO);
+    }
+}
+
+/// Runs `f` with the standard output and error of the process redirected to
+/// a pipe, and returns its result together with what it printed.
+///
+/// The file descriptors are shared by the whole process, so what other
+/// threads print meanwhile is captured as well.
+pub(crate) fn capture<T>(f: impl FnOnce() -> T) -> io::Result<(T, String)> {
+    let _guard = LOCK.lock().unwrap_or_else(|err| err.into_inner());
+
+    let mut fds = [0; 2];
+    if unsafe { pipe(fds.as_mut_ptr()) } != 0 {
+        return Err(io::Error::last_os_error());
+    }
+    let mut reader = unsafe { File::from_raw_fd(fds[0]) };
+    let writer = unsafe { OwnedFd::from_raw_fd(fds[1]) };
+
+    // The pipe is drained while `f` runs, so that it never blocks on a full
+    // pipe.
+    let drain = thread::spawn(move || {
+        let mut output = Vec::new();
+        reader.read_to_end(&mut output).map(|_| output)
+    });
+
+    unsafe { fflush(ptr::null_mut()) };
+    let stdout = dup_owned(STDOUT_FILENO)?;
+    let stderr = dup_owned(STDERR_FILENO)?;
+    redirect(writer.as_raw_fd(), STDOUT_FILENO)?;
+    let restore = Restore { stdout, stderr };
+    redirect(writer.as_raw_fd(), STDERR_FILENO)?;
+    // The standard output and error now hold the only writers.
+    drop(writer);
+
+    let result = f();
+    drop(restore);
+
+    let output = drain.join().unwrap()?;
+    Ok((result, String::from_utf8_lossy(&output).into_owned()))
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::{capture, LOCK};
+    use std::fs;
+    use std::os::raw::{c_int, c_void};
+    use std::panic;
+
+    extern "C" {
+        fn write(fd: c_int, buf: *const c_void, count: usize) -> isize;
+    }
+
+    #[test]
+    fn captures_stdout_and_stderr() {
+        let (value, output) = capture(|| {
+            unsafe {
+                write(1, b"out\n".as_ptr() as *const c_void, 4);
+                write(2, b"err\n".as_ptr() as *const c_void, 4);
+            }
+            42
+        })
+        .unwrap();
+
+        assert_eq!(value, 42);
+        assert_eq!(output, "out\nerr\n");
+    }
+
+    #[test]
+    fn captures_large_output() {
+        let line = [b'x'; 1024];
+        let (_, output) = capture(|| {
+            for _ in 0..1024 {
+                unsafe { write(1, line.as_ptr() as *const c_void, line.len()) };
+            }
+        })
+        .unwrap();
+
+        assert_eq!(output.len(), 1024 * 1024);
+    }
+
+    #[test]
+    fn restores_on_panic() {
+        // What the standard output is, while no capture runs.
+        let stdout = || {
+            let _guard = LOCK.lock().unwrap_or_else(|err| err.into_inner());
+            fs::read_link("/proc/self/fd/1").ok()
+        };
+        let before = stdout();
+
+        let result = panic::catch_unwind(|| {
+            capture(|| {
+                unsafe { write(1, b"lost\n".as_ptr() as *const c_void, 5) };
+                panic!("panic during a capture");
+            })
+        });
+        assert!(result.is_err());
+        assert_eq!(stdout(), before);
+
+        let (_, output) = capture(|| unsafe {
+            write(1, b"out\n".as_ptr() as *const c_void, 4);
+        })
+        .unwrap();
+        assert_eq!(output, "out\n");
+    }
+}
@@ -0,0 +1,509 @@
+//! Options of a partition computation.
+
+use crate::{checked_idx, Idx, Mode, PartitionError, Quality};
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+
+/// The allowed imbalance of a partition.
+///
+/// KaHIP bounds the weight of every block by `(1 + ε) * ⌈W / k⌉`, where `W` is
+/// the total vertex weight, `k` the number of blocks and `ε` the imbalance as
+/// a fraction: an imbalance of 3% is `ε = 0.03`, not `ε = 3`. This type is
+/// built from either form and always holds the fraction.
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
+pub struct Imbalance(f64);
+
+impl Imbalance {
+    /// Creates an imbalance from a percentage, e.g. `3.0` for 3%.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if `percent` is negative or not finite.
+    pub fn percent(percent: f64) -> Imbalance {
+        Imbalance::try_percent(percent).unwrap()
+    }
+
+    /// Creates an imbalance from a fraction, e.g. `0.03` for 3%.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if `fraction` is negative or not finite.
+    pub fn fraction(fraction: f64) -> Imbalance {
+        Imbalance::try_fraction(fraction).unwrap()
+    }
+
+    /// Same as [`Imbalance::percent`], returning an error if `percent` is
+    /// negative or not finite.
+    pub fn try_percent(percent: f64) -> Result<Imbalance, PartitionError> {
+        Imbalance::try_fraction(percent / 100.0)
+            .map_err(|_| PartitionError::InvalidImbalance { imbalance: percent })
+    }
+
+    /// Same as [`Imbalance::fraction`], returning an error if `fraction` is
+    /// negative or not finite.
+    pub fn try_fraction(fraction: f64) -> Result<Imbalance, PartitionError> {
+        if !fraction.is_finite() || fraction < 0.0 {
+            return Err(PartitionError::InvalidImbalance {
+                imbalance: fraction,
+            });
+        }
+        Ok(Imbalance(fraction))
+    }
+
+    /// The imbalance as a percentage.
+    pub fn as_percent(self) -> f64 {
+        self.0 * 100.0
+    }
+
+    /// The imbalance as a fraction, as given to KaHIP.
+    pub fn as_fraction(self) -> f64 {
+        self.0
+    }
+}
+
+impl From<Imbalance> for f64 {
+    fn from(imbalance: Imbalance) -> f64 {
+        imbalance.0
+    }
+}
+
+/// The seed of KaHIP's random number generator.
+///
+/// Every [`Mode`] consumes the seed: KaHIP breaks ties at random while
+/// coarsening, computing initial partitions and refining, so different seeds
+/// usually give different partitions of similar quality. The node separator,
+/// nested dissection and process mapping entry points take one too. KaHIP's
+/// partitioner is sequential, so a fixed seed reproduces the same partition
+/// for the same graph and options, but only with the same build of KaHIP.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Seed(Idx);
+
+impl Seed {
+    /// Creates a seed with the given value, to reproduce a partition.
+    pub fn fixed(seed: Idx) -> Seed {
+        Seed(seed)
+    }
+
+    /// Creates a seed from the randomness of the operating system, to get a
+    /// different partition at every run.
+    ///
+    /// The value is derived from the keys of the standard library's hasher,
+    /// which the operating system seeds once per thread and which are then
+    /// advanced for every call. It is fast, but not suitable for cryptography.
+    pub fn from_entropy() -> Seed {
+        let hash = RandomState::new().build_hasher().finish();
+        Seed(hash as Idx)
+    }
+
+    /// The value of the seed, as given to KaHIP.
+    pub fn value(self) -> Idx {
+        self.0
+    }
+}
+
+impl From<Idx> for Seed {
+    fn from(seed: Idx) -> Seed {
+        Seed(seed)
+    }
+}
+
+impl From<Seed> for Idx {
+    fn from(seed: Seed) -> Idx {
+        seed.0
+    }
+}
+
+/// How far from 1 the sum of the target weights of
+/// [`PartitionConfig::set_target_weights`] may be, to allow for rounding.
+pub const TARGET_WEIGHTS_TOLERANCE: f64 = 1e-6;
+
+/// The arguments of [`Graph::partition_with`](crate::Graph::partition_with).
+///
+/// Only the number of blocks is required, every other option has a default:
+///
+/// ```
+/// # use kahip::{Mode, PartitionConfig};
+/// let cfg = PartitionConfig::new(4).set_seed(1234).set_mode(Mode::Strong);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PartitionConfig {
+    pub(crate) n_parts: Idx,
+    pub(crate) imbalance: f64,
+    pub(crate) suppress_output: bool,
+    pub(crate) seed: Idx,
+    pub(crate) mode: Mode,
+    pub(crate) target_weights: Option<Vec<f64>>,
+}
+
+impl PartitionConfig {
+    /// Creates a new [`PartitionConfig`] to split a graph into `n_parts`
+    /// blocks.
+    ///
+    /// The imbalance is 3%, KaHIP's output is suppressed, the seed is 0 and
+    /// the mode is [`Mode::Eco`].
+    pub fn new(n_parts: Idx) -> PartitionConfig {
+        PartitionConfig {
+            n_parts,
+            imbalance: 0.03,
+            suppress_output: true,
+            seed: 0,
+            mode: Mode::Eco,
+            target_weights: None,
+        }
+    }
+
+    /// Sets the allowed imbalance, either as a fraction, e.g. `0.03` for 3%,
+    /// or as an [`Imbalance`].
+    pub fn set_imbalance(mut self, imbalance: impl Into<f64>) -> PartitionConfig {
+        self.imbalance = imbalance.into();
+        self
+    }
+
+    /// Sets whether KaHIP's output is suppressed.
+    pub fn set_suppress_output(mut self, suppress_output: bool) -> PartitionConfig {
+        self.suppress_output = suppress_output;
+        self
+    }
+
+    /// Sets the seed of KaHIP's random number generator, either as an
+    /// integer or as a [`Seed`].
+    pub fn set_seed(mut self, seed: impl Into<Seed>) -> PartitionConfig {
+        self.seed = seed.into().value();
+        self
+    }
+
+    /// Sets the preconfiguration used by KaHIP.
+    pub fn set_mode(mut self, mode: Mode) -> PartitionConfig {
+        self.mode = mode;
+        self
+    }
+
+    /// Sets the quality of the preconfiguration, keeping whether it is tuned
+    /// for social networks.
+    pub fn set_quality(mut self, quality: Quality) -> PartitionConfig {
+        self.mode = Mode::new(quality, self.mode.is_social());
+        self
+    }
+
+    /// Sets whether the preconfiguration is tuned for social networks,
+    /// keeping its quality.
+    pub fn set_social(mut self, social: bool) -> PartitionConfig {
+        self.mode = Mode::new(self.mode.quality(), social);
+        self
+    }
+
+    /// Sets the fraction of the total vertex weight meant for each block.
+    ///
+    /// By default all blocks have the same target weight. The fractions must
+    /// sum to 1, up to [`TARGET_WEIGHTS_TOLERANCE`].
+    ///
+    /// KaHIP's interface has no such parameter: the graph is instead split
+    /// into more blocks of equal weight, which are then merged, e.g. into
+    /// 2 + 1 + 1 blocks for targets `[0.5, 0.25, 0.25]`. Merged blocks may not
+    /// be adjacent, so the edge cut is usually higher than with a direct
+    /// method, and targets that are not close to a fraction with a small
+    /// denominator are only approximated.
+    pub fn set_target_weights(mut self, target_weights: &[f64]) -> PartitionConfig {
+        self.target_weights = Some(target_weights.to_vec());
+        self
+    }
+
+    /// Checks that this configuration can partition a graph of `nvtxs`
+    /// vertices, before calling [`Graph::try_partition_with`](crate::Graph::try_partition_with).
+    ///
+    /// Returns an error if:
+    /// - the number of blocks is not between 1 and `nvtxs`, unless the graph
+    ///   is empty, which can be split into any number of empty blocks,
+    /// - the imbalance is negative or not finite, or
+    /// - the target weights are set, but not one per block, one of them is
+    ///   not positive, or they do not sum to 1 up to
+    ///   [`TARGET_WEIGHTS_TOLERANCE`].
+    ///
+    /// The checks that depend on the graph itself, such as its vertex
+    /// weights or its fixed vertices, are left to the partition.
+    pub fn validate(&self, nvtxs: usize) -> Result<(), PartitionError> {
+        let n_vertices = checked_idx(nvtxs)?;
+        if !self.imbalance.is_finite() || self.imbalance < 0.0 {
+            return Err(PartitionError::InvalidImbalance {
+                imbalance: self.imbalance,
+            });
+        }
+        if self.n_parts < 1 || (n_vertices > 0 && self.n_parts > n_vertices) {
+            return Err(PartitionError::InvalidNParts {
+                n_parts: self.n_parts,
+                n_vertices,
+            });
+        }
+        if let Some(targets) = &self.target_weights {
+            if targets.len() != self.n_parts as usize {
+                return Err(PartitionError::TargetWeightsLengthMismatch {
+                    expected: self.n_parts as usize,
+                    got: targets.len(),
+                });
+            }
+            if let Some((block, &value)) = targets
+                .iter()
+                .enumerate()
+                .find(|(_, &t)| t.is_nan() || t <= 0.0)
+            {
+                return Err(PartitionError::NonPositiveTargetWeight { block, value });
+            }
+            let sum: f64 = targets.iter().sum();
+            if (sum - 1.0).abs() > TARGET_WEIGHTS_TOLERANCE {
+                return Err(PartitionError::TargetWeightsSum { sum });
+            }
+        }
+        Ok(())
+    }
+
+    /// The number of blocks.
+    pub fn n_parts(&self) -> Idx {
+        self.n_parts
+    }
+
+    /// The allowed imbalance.
+    pub fn imbalance(&self) -> f64 {
+        self.imbalance
+    }
+
+    /// Whether KaHIP's output is suppressed.
+    pub fn suppress_output(&self) -> bool {
+        self.suppress_output
+    }
+
+    /// The seed of KaHIP's random number generator.
+    pub fn seed(&self) -> Idx {
+        self.seed
+    }
+
+    /// The preconfiguration used by KaHIP.
+    pub fn mode(&self) -> Mode {
+        self.mode
+    }
+
+    /// The quality of the preconfiguration.
+    pub fn quality(&self) -> Quality {
+        self.mode.quality()
+    }
+
+    /// Whether the preconfiguration is tuned for social networks.
+    pub fn social(&self) -> bool {
+        self.mode.is_social()
+    }
+
+    /// The fraction of the total vertex weight meant for each block, if set.
+    pub fn target_weights(&self) -> Option<&[f64]> {
+        self.target_weights.as_deref()
+    }
+}
+
+/// The number of totals [`sub_blocks`] tries at most, each of them costing a
+/// sort of the targets.
+const MAX_SUB_BLOCK_TRIES: usize = 256;
+
+/// Splits `max_parts` or less equal blocks between the given targets.
+///
+/// Returns the number of equal blocks given to each target. The first split
+/// within `tolerance` of the targets is used, or the closest one otherwise.
+/// At most [`MAX_SUB_BLOCK_TRIES`] totals are tried, evenly spaced between
+/// the number of targets and `max_parts`.
+pub(crate) fn sub_blocks(targets: &[f64], max_parts: Idx, tolerance: f64) -> Vec<Idx> {
+    let n_parts = targets.len() as Idx;
+    let total: f64 = targets.iter().sum();
+    let step = (max_parts.saturating_sub(n_parts).max(0) as usize)
+        .div_ceil(MAX_SUB_BLOCK_TRIES)
+        .max(1);
+
+    let mut best = (f64::INFINITY, vec![1; targets.len()]);
+    for q in (n_parts..=max_parts).step_by(step) {
+        let ideal: Vec<f64> = targets.iter().map(|t| t / total * q as f64).collect();
+        let mut counts: Vec<Idx> = ideal.iter().map(|&x| (x as Idx).max(1)).collect();
+        let remaining = q - counts.iter().sum::<Idx>();
+        if remaining < 0 {
+            continue;
+        }
+
+        // Largest remainder method.
+        let mut order: Vec<usize> = (0..targets.len()).collect();
+        order.sort_by(|&i, &j| {
+            let ri = ideal[i] - counts[i] as f64;
+            let rj = ideal[j] - counts[j] as f64;
+            rj.total_cmp(&ri)
+        });
+        for &i in &order[..remaining as usize] {
+            counts[i] += 1;
+        }
+
+        let error = counts
+            .iter()
+            .zip(&ideal)
+            .map(|(&c, &x)| (c as f64 - x).abs() / x)
+            .fold(0.0, f64::max);
+        if error < best.0 {
+            best = (error, counts);
+        }
+        if best.0 <= tolerance {
+            break;
+        }
+    }
+    best.1
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::sub_blocks;
+    use crate::testgraphs::grid_2d;
+    use crate::{Idx, Imbalance, Mode, PartitionConfig, PartitionError, Quality, Seed};
+
+    #[test]
+    fn validate() {
+        assert_eq!(PartitionConfig::new(4).validate(10), Ok(()));
+        assert_eq!(PartitionConfig::new(10).validate(10), Ok(()));
+        assert_eq!(PartitionConfig::new(3).validate(0), Ok(()));
+
+        assert_eq!(
+            PartitionConfig::new(0).validate(10),
+            Err(PartitionError::InvalidNParts {
+                n_parts: 0,
+                n_vertices: 10
+            })
+        );
+        assert_eq!(
+            PartitionConfig::new(11).validate(10),
+            Err(PartitionError::InvalidNParts {
+                n_parts: 11,
+                n_vertices: 10
+            })
+        );
+        assert_eq!(
+            PartitionConfig::new(2).set_imbalance(-0.1).validate(10),
+            Err(PartitionError::InvalidImbalance { imbalance: -0.1 })
+        );
+        assert_eq!(
+            PartitionConfig::new(2)
+                .set_imbalance(f64::INFINITY)
+                .validate(10),
+            Err(PartitionError::InvalidImbalance {
+                imbalance: f64::INFINITY
+            })
+        );
+        assert!(matches!(
+            PartitionConfig::new(2).set_imbalance(f64::NAN).validate(10),
+            Err(PartitionError::InvalidImbalance { .. })
+        ));
+
+        let cfg = PartitionConfig::new(2).set_target_weights(&[0.2, 0.3, 0.5]);
+        assert_eq!(
+            cfg.validate(10),
+            Err(PartitionError::TargetWeightsLengthMismatch {
+                expected: 2,
+                got: 3
+            })
+        );
+        let cfg = PartitionConfig::new(2).set_target_weights(&[1.0, 0.0]);
+        assert_eq!(
+            cfg.validate(10),
+            Err(PartitionError::NonPositiveTargetWeight {
+                block: 1,
+                value: 0.0
+            })
+        );
+        let cfg = PartitionConfig::new(2).set_target_weights(&[2.0, 1.0]);
+        assert_eq!(
+            cfg.validate(10),
+            Err(PartitionError::TargetWeightsSum { sum: 3.0 })
+        );
+        let cfg = PartitionConfig::new(3).set_target_weights(&[0.3, 0.3, 0.3]);
+        assert!(matches!(
+            cfg.validate(10),
+            Err(PartitionError::TargetWeightsSum { .. })
+        ));
+        let cfg = PartitionConfig::new(3).set_target_weights(&[0.1, 0.2, 0.7]);
+        assert_eq!(cfg.validate(10), Ok(()));
+    }
+
+    #[test]
+    fn imbalance() {
+        assert_eq!(Imbalance::percent(3.0), Imbalance::fraction(0.03));
+        assert_eq!(Imbalance::percent(3.0).as_fraction(), 0.03);
+        assert_eq!(Imbalance::fraction(0.5).as_percent(), 50.0);
+        assert_eq!(Imbalance::percent(0.0).as_fraction(), 0.0);
+
+        assert_eq!(
+            Imbalance::try_percent(-1.0),
+            Err(PartitionError::InvalidImbalance { imbalance: -1.0 })
+        );
+        assert!(Imbalance::try_fraction(f64::NAN).is_err());
+        assert!(Imbalance::try_fraction(f64::INFINITY).is_err());
+
+        let cfg = PartitionConfig::new(2).set_imbalance(Imbalance::percent(5.0));
+        assert_eq!(cfg.imbalance(), 0.05);
+    }
+
+    #[test]
+    fn sub_blocks_of_targets() {
+        assert_eq!(sub_blocks(&[0.5, 0.25, 0.25], 100, 0.03), [2, 1, 1]);
+        assert_eq!(sub_blocks(&[2.0, 1.0, 1.0], 100, 0.03), [2, 1, 1]);
+        assert_eq!(sub_blocks(&[0.3, 0.7], 100, 0.03), [3, 7]);
+        assert_eq!(sub_blocks(&[1.0, 1.0], 100, 0.03), [1, 1]);
+        // Not enough blocks to get close, the best split is kept.
+        assert_eq!(sub_blocks(&[0.1, 0.9], 4, 0.03), [1, 3]);
+
+        // Many targets far from simple fractions, with a bounded number of
+        // tries.
+        let targets: Vec<f64> = (0..2000)
+            .map(|i| 1.0 + (i as f64 * 0.618).fract())
+            .collect();
+        let counts = sub_blocks(&targets, 32 * 2000, 0.0);
+        assert_eq!(counts.len(), 2000);
+        assert!(counts.iter().all(|&c| c >= 1));
+        assert!(counts.iter().sum::<Idx>() <= 32 * 2000);
+    }
+
+    #[test]
+    fn quality_and_social() {
+        let cfg = PartitionConfig::new(4);
+        assert_eq!((cfg.quality(), cfg.social()), (Quality::Eco, false));
+
+        let cfg = cfg.set_social(true);
+        assert_eq!(cfg.mode(), Mode::EcoSocial);
+        let cfg = cfg.set_quality(Quality::Strong);
+        assert_eq!(cfg.mode(), Mode::StrongSocial);
+        let cfg = cfg.set_social(false);
+        assert_eq!(cfg.mode(), Mode::Strong);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_round_trip() {
+        let defaults = PartitionConfig::new(4);
+        let custom = PartitionConfig::new(3)
+            .set_imbalance(0.1)
+            .set_seed(1234)
+            .set_mode(Mode::StrongSocial)
+            .set_target_weights(&[0.5, 0.25, 0.25]);
+
+        for cfg in [defaults, custom] {
+            let json = serde_json::to_string(&cfg).unwrap();
+            assert_eq!(serde_json::from_str::<PartitionConfig>(&json).unwrap(), cfg);
+        }
+    }
+
+    #[test]
+    fn seed() {
+        let cfg = PartitionConfig::new(4).set_seed(Seed::fixed(1234));
+        assert_eq!(cfg, PartitionConfig::new(4).set_seed(1234));
+        assert_eq!(cfg.seed(), Seed::fixed(1234).value());
+
+        let mut graph = grid_2d(10, 10);
+        let first = graph.as_graph().partition_with(&cfg);
+        let second = graph.as_graph().partition_with(&cfg);
+        assert_eq!(first, second);
+
+        // Collisions are possible but astronomically unlikely.
+        let seeds: Vec<Seed> = (0..4).map(|_| Seed::from_entropy()).collect();
+        assert!(seeds[1..].iter().any(|&seed| seed != seeds[0]));
+    }
+}
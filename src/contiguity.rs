@@ -0,0 +1,264 @@
+//! Connectivity of graphs and of the blocks of partitions.
+
+use crate::{GraphRef, Idx};
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Labels the connected components of the subgraphs induced by the blocks
+/// of `part`, i.e. two vertices have the same label when they are in the
+/// same block and connected by a path inside that block.
+///
+/// Returns the label of each vertex and the number of components. Labels are
+/// numbered in order of the smallest vertex of each component.
+pub(crate) fn block_components(graph: &GraphRef, part: &[Idx]) -> (Vec<usize>, usize) {
+    let mut label = vec![usize::MAX; part.len()];
+    let mut count = 0;
+    let mut stack = Vec::new();
+    for root in 0..part.len() {
+        if label[root] != usize::MAX {
+            continue;
+        }
+        label[root] = count;
+        stack.push(root);
+        while let Some(v) = stack.pop() {
+            for &u in &graph.adjncy[graph.xadj[v] as usize..graph.xadj[v + 1] as usize] {
+                let u = u as usize;
+                if label[u] == usize::MAX && part[u] == part[v] {
+                    label[u] = count;
+                    stack.push(u);
+                }
+            }
+        }
+        count += 1;
+    }
+    (label, count)
+}
+
+/// Returns the number of connected components of `graph`, and the component
+/// of each vertex.
+///
+/// Components are numbered from 0, in order of their smallest vertex. The
+/// graph is connected when there is a single component. Edges are followed
+/// in the direction they are stored, so the graph should be symmetric.
+pub fn connected_components<'g>(graph: impl Into<GraphRef<'g>>) -> (usize, Vec<Idx>) {
+    let graph = graph.into();
+    let (label, count) = block_components(&graph, &vec![0; graph.num_vertices()]);
+    (count, label.into_iter().map(|c| c as Idx).collect())
+}
+
+/// Labels the vertices of a node separator with `n_parts`, and the others
+/// with a block in `0..n_parts` such that no edge joins two different blocks.
+///
+/// The connected components left once the separator is removed are assigned,
+/// from the heaviest to the lightest, to the block of least vertex weight.
+pub(crate) fn separator_labels(graph: GraphRef, separator: &[Idx], n_parts: Idx) -> Vec<Idx> {
+    let mut label = vec![0; graph.num_vertices()];
+    for &v in separator {
+        label[v as usize] = n_parts;
+    }
+    let (component, count) = block_components(&graph, &label);
+    let vwgt = graph.vwgt.filter(|_| graph.ncon == 1);
+
+    let mut weight = vec![0i64; count];
+    let mut in_separator = vec![false; count];
+    for (v, &c) in component.iter().enumerate() {
+        weight[c] += vwgt.map_or(1, |vwgt| vwgt[v] as i64);
+        in_separator[c] = label[v] == n_parts;
+    }
+    let mut order: Vec<usize> = (0..count).filter(|&c| !in_separator[c]).collect();
+    order.sort_by_key(|&c| (Reverse(weight[c]), c));
+
+    let mut block_of = vec![n_parts; count];
+    let mut block_weight = vec![0i64; n_parts as usize];
+    for c in order {
+        let block = (0..block_weight.len())
+            .min_by_key(|&b| (block_weight[b], b))
+            .unwrap();
+        block_of[c] = block as Idx;
+        block_weight[block] += weight[c];
+    }
+    component.into_iter().map(|c| block_of[c]).collect()
+}
+
+/// Makes every block of a partition connected, moving the smaller fragments
+/// of disconnected blocks to neighboring blocks. Returns the number of
+/// vertices that were moved.
+///
+/// KaHIP's k-way partitioner does not guarantee connected blocks. This keeps
+/// the heaviest connected fragment of each block, by vertex weight (or vertex
+/// count if the graph has no or several vertex weights), and moves each other
+/// fragment as a whole to the adjacent block it shares the most edge weight
+/// with. Fragments are merged from the lightest to the heaviest, until every
+/// block is connected. Fragments are small in practice, but moving them may
+/// still increase the imbalance and the edge cut of the partition.
+///
+/// A fragment that has no neighbor outside of its block is a connected
+/// component of the graph itself, and is left where it is: the blocks of a
+/// disconnected graph cannot all be connected.
+///
+/// # Panics
+///
+/// This function panics if the length of `part` is different than the number
+/// of vertices.
+pub fn enforce_contiguity<'g>(graph: impl Into<GraphRef<'g>>, part: &mut [Idx]) -> usize {
+    let graph = graph.into();
+    assert_eq!(part.len(), graph.xadj.len() - 1);
+    let vwgt = graph.vwgt.filter(|_| graph.ncon == 1);
+
+    let mut moved = 0;
+    loop {
+        let (label, count) = block_components(&graph, part);
+        let mut weight = vec![0i64; count];
+        let mut block = vec![0; count];
+        for v in 0..part.len() {
+            weight[label[v]] += vwgt.map_or(1, |vwgt| vwgt[v] as i64);
+            block[label[v]] = part[v];
+        }
+
+        // The heaviest fragment of each block stays, the first on ties.
+        let mut main = BTreeMap::new();
+        for c in 0..count {
+            main.entry(block[c])
+                .and_modify(|m: &mut usize| {
+                    if weight[c] > weight[*m] {
+                        *m = c;
+                    }
+                })
+                .or_insert(c);
+        }
+        let mut fragments: Vec<usize> = (0..count).filter(|&c| main[&block[c]] != c).collect();
+        if fragments.is_empty() {
+            return moved;
+        }
+        fragments.sort_by_key(|&c| (weight[c], c));
+
+        // The edge weight between each fragment and the adjacent blocks.
+        let mut shared: Vec<BTreeMap<Idx, i64>> = vec![BTreeMap::new(); count];
+        for v in 0..part.len() {
+            let c = label[v];
+            if main[&block[c]] == c {
+                continue;
+            }
+            for k in graph.xadj[v] as usize..graph.xadj[v + 1] as usize {
+                let u = graph.adjncy[k] as usize;
+                if part[u] != part[v] {
+                    let w = graph.adjwgt.map_or(1, |adjwgt| adjwgt[k] as i64);
+                    *shared[c].entry(part[u]).or_insert(0) += w;
+                }
+            }
+        }
+
+        // Moving a fragment changes the fragments of its old and new blocks,
+        // so each block takes part in at most one move per round.
+        let mut touched = BTreeSet::new();
+        let mut progress = false;
+        for c in fragments {
+            if touched.contains(&block[c]) {
+                continue;
+            }
+            let target = shared[c]
+                .iter()
+                .filter(|(b, _)| !touched.contains(*b))
+                .max_by_key(|&(&b, &w)| (w, Reverse(b)))
+                .map(|(&b, _)| b);
+            let Some(target) = target else {
+                continue;
+            };
+            for v in 0..part.len() {
+                if label[v] == c {
+                    part[v] = target;
+                    moved += 1;
+                }
+            }
+            touched.insert(block[c]);
+            touched.insert(target);
+            progress = true;
+        }
+        if !progress {
+            return moved;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::{block_components, separator_labels};
+    use crate::testgraphs::grid_2d;
+    use crate::{connected_components, edge_cut, enforce_contiguity, Graph, GraphRef, Idx};
+
+    /// Whether every block of `part` is connected.
+    fn is_contiguous(graph: GraphRef, part: &[Idx], n_parts: Idx) -> bool {
+        let (_, count) = block_components(&graph, part);
+        count == n_parts as usize
+    }
+
+    #[test]
+    fn components() {
+        let mut xadj = vec![0, 2, 5, 7, 9, 12];
+        let mut adjncy = vec![1, 4, 0, 2, 4, 1, 3, 2, 4, 0, 1, 3];
+        let graph = Graph::new(&mut xadj, &mut adjncy);
+        assert_eq!(connected_components(&graph), (1, vec![0; 5]));
+
+        // Two cliques of three and four vertices, interleaved.
+        let xadj = [0, 2, 5, 7, 10, 12, 15, 18];
+        let adjncy = [2, 4, 3, 5, 6, 0, 4, 1, 5, 6, 0, 2, 1, 3, 6, 1, 3, 5];
+        let (count, label) = connected_components(GraphRef::new(&xadj, &adjncy));
+        assert_eq!(count, 2);
+        assert_eq!(label, [0, 1, 0, 1, 0, 1, 1]);
+    }
+
+    #[test]
+    fn separator() {
+        // A path of 7 vertices, cut in the middle.
+        let xadj = [0, 1, 3, 5, 7, 9, 11, 12];
+        let adjncy = [1, 0, 2, 1, 3, 2, 4, 3, 5, 4, 6, 5];
+        let graph = GraphRef::new(&xadj, &adjncy);
+        assert_eq!(separator_labels(graph, &[3], 2), [0, 0, 0, 2, 1, 1, 1]);
+
+        // Three components for two blocks, the lightest one goes to the first
+        // block on ties.
+        assert_eq!(separator_labels(graph, &[1, 4], 2), [0, 2, 0, 0, 2, 1, 1]);
+    }
+
+    #[test]
+    fn contiguous_blocks() {
+        // A 6x6 grid split into its left and right halves, with a stray
+        // vertex of block 1 on the left, and two of block 0 on the right.
+        let graph = grid_2d(6, 6);
+        let mut part: Vec<Idx> = (0..36).map(|v| (v % 6 >= 3) as Idx).collect();
+        part[7] = 1;
+        part[28] = 0;
+        part[22] = 0;
+        assert!(!is_contiguous((&graph).into(), &part, 2));
+
+        let moved = enforce_contiguity(&graph, &mut part);
+        assert_eq!(moved, 3);
+        assert!(is_contiguous((&graph).into(), &part, 2));
+        let halves: Vec<Idx> = (0..36).map(|v| (v % 6 >= 3) as Idx).collect();
+        assert_eq!(part, halves);
+        assert_eq!(edge_cut(&graph, &part), 6);
+
+        // Already contiguous partitions are left untouched.
+        assert_eq!(enforce_contiguity(&graph, &mut part), 0);
+        assert_eq!(part, halves);
+    }
+
+    #[test]
+    fn disconnected_graph() {
+        // Two triangles, the second one split between the two blocks.
+        let xadj = [0, 2, 4, 6, 8, 10, 12];
+        let adjncy = [1, 2, 0, 2, 0, 1, 4, 5, 3, 5, 3, 4];
+        let graph = GraphRef::new(&xadj, &adjncy);
+        let mut part = [0, 0, 0, 1, 0, 1];
+
+        // Vertex 4 joins block 1, which is then the second triangle.
+        assert_eq!(enforce_contiguity(graph, &mut part), 1);
+        assert_eq!(part, [0, 0, 0, 1, 1, 1]);
+
+        // Block 1 is empty, the triangles of block 0 have nowhere to go.
+        let mut part = [0; 6];
+        assert_eq!(enforce_contiguity(graph, &mut part), 0);
+        assert_eq!(part, [0; 6]);
+    }
+}
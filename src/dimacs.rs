@@ -0,0 +1,171 @@
+//! Reading graphs in the DIMACS shortest path format.
+
+use crate::metis::{invalid, next_count, next_int};
+use crate::{GraphBuf, Idx, WeightMerge};
+use std::io::{self, BufRead};
+
+impl GraphBuf {
+    /// Reads a graph in the DIMACS shortest path format (`.gr`), as used by
+    /// the road networks of the 9th DIMACS challenge.
+    ///
+    /// The problem line `p sp n m` gives the number of vertices and arcs,
+    /// then each arc line `a u v w` gives an arc from `u` to `v` of weight
+    /// `w`. Vertex ids start at 1 in the file and at 0 in the returned graph.
+    /// Lines starting with `c` are comments.
+    ///
+    /// KaHIP needs undirected graphs, so arcs are read as edges: `u -> v` and
+    /// `v -> u` give a single edge, which keeps the largest of their weights
+    /// as with [`WeightMerge::Max`]. A road stored as two arcs of the same
+    /// weight thus keeps that weight. Self-loops are dropped.
+    pub fn read_dimacs<R: BufRead>(reader: R) -> io::Result<GraphBuf> {
+        let mut problem = None;
+        let mut arcs = Vec::new();
+        for (i, line) in reader.lines().enumerate() {
+            let (number, line) = (i + 1, line?);
+            let mut tokens = line.split_whitespace();
+            match tokens.next() {
+                None | Some("c") => {}
+                Some("p") if problem.is_some() => {
+                    return Err(invalid(format!("line {number}: duplicate problem line")))
+                }
+                Some("p") => {
+                    if tokens.next() != Some("sp") {
+                        return Err(invalid(format!(
+                            "line {number}: expected a shortest path problem"
+                        )));
+                    }
+                    let n_vertices = next_count(&mut tokens, number, "number of vertices")?;
+                    let n_arcs = next_count(&mut tokens, number, "number of arcs")?;
+                    problem = Some((number, n_vertices, n_arcs));
+                }
+                Some("a") => {
+                    let Some((_, n_vertices, _)) = problem else {
+                        return Err(invalid(format!("line {number}: arc before problem line")));
+                    };
+                    let mut vertex = |what| {
+                        let v = next_int(&mut tokens, number, what)?;
+                        if !(1..=n_vertices).contains(&v) {
+                            return Err(invalid(format!(
+                                "line {number}: {what} {v} is not a vertex"
+                            )));
+                        }
+                        Ok(v - 1)
+                    };
+                    let u = vertex("tail")?;
+                    let v = vertex("head")?;
+                    let w = next_int(&mut tokens, number, "arc weight")?;
+                    arcs.push((u, v, w));
+                }
+                Some(token) => {
+                    return Err(invalid(format!(
+                        "line {number}: unknown line type {token:?}"
+                    )))
+                }
+            }
+        }
+
+        let (problem_line, n_vertices, n_arcs) =
+            problem.ok_or_else(|| invalid("missing problem line".to_string()))?;
+        if arcs.len() != n_arcs as usize {
+            return Err(invalid(format!(
+                "expected {n_arcs} arcs, found {}",
+                arcs.len()
+            )));
+        }
+
+        // Directed CSR of the arcs, symmetrized below.
+        // The number of vertices is not bounded by the length of the file,
+        // so failing to allocate them is an error rather than an abort.
+        let too_many = || {
+            invalid(format!(
+                "line {problem_line}: too many vertices {n_vertices}"
+            ))
+        };
+        let n_vertices = usize::try_from(n_vertices).map_err(|_| too_many())?;
+        let mut xadj = Vec::new();
+        xadj.try_reserve_exact(n_vertices + 1)
+            .map_err(|_| too_many())?;
+        xadj.resize(n_vertices + 1, 0);
+        for &(u, _, _) in &arcs {
+            xadj[u as usize + 1] += 1;
+        }
+        for v in 0..n_vertices {
+            xadj[v + 1] += xadj[v];
+        }
+        let mut next: Vec<Idx> = xadj[..n_vertices].to_vec();
+        let mut adjncy = vec![0; arcs.len()];
+        let mut adjwgt = vec![0; arcs.len()];
+        for (u, v, w) in arcs {
+            let k = next[u as usize] as usize;
+            adjncy[k] = v;
+            adjwgt[k] = w;
+            next[u as usize] += 1;
+        }
+
+        let directed = GraphBuf::new(xadj, adjncy).set_adjwgt(adjwgt);
+        Ok(directed.symmetrize(WeightMerge::Max))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use crate::GraphBuf;
+
+    /// The sample graph, with the edge 2-3 given in a single direction, the
+    /// edge 3-4 with two different weights, and a self-loop.
+    const SAMPLE: &str = "\
+c The sample graph.
+p sp 5 12
+a 1 2 1
+a 1 5 2
+a 2 1 1
+a 2 5 4
+a 3 2 3
+a 3 4 5
+a 4 3 4
+a 4 5 6
+a 5 1 2
+a 5 2 4
+a 5 4 6
+a 3 3 7
+";
+
+    #[test]
+    fn read_dimacs() {
+        let graph = GraphBuf::read_dimacs(SAMPLE.as_bytes()).unwrap();
+
+        assert_eq!(graph.xadj(), [0, 2, 5, 7, 9, 12]);
+        assert_eq!(graph.adjncy(), [1, 4, 0, 2, 4, 1, 3, 2, 4, 0, 1, 3]);
+        assert_eq!(
+            graph.adjwgt(),
+            Some(&[1, 2, 1, 3, 4, 3, 5, 5, 6, 2, 4, 6][..])
+        );
+        assert_eq!(graph.vwgt(), None);
+    }
+
+    #[test]
+    fn read_dimacs_invalid() {
+        for (file, message) in [
+            ("a 1 2 1\n", "line 1: arc before problem line"),
+            ("p sp 2 1\na 1 3 1\n", "line 2: head 3 is not a vertex"),
+            ("p sp 2 2\na 1 2 1\n", "expected 2 arcs, found 1"),
+            ("p max 2 1\n", "line 1: expected a shortest path problem"),
+            ("p sp 2 1\nx\n", "line 2: unknown line type \"x\""),
+            ("c empty\n", "missing problem line"),
+            ("p sp -1 0\n", "line 1: negative number of vertices -1"),
+            ("p sp 2 -1\n", "line 1: negative number of arcs -1"),
+        ] {
+            let error = GraphBuf::read_dimacs(file.as_bytes()).unwrap_err();
+            assert_eq!(error.to_string(), message);
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "idx64")]
+    fn read_dimacs_too_many_vertices() {
+        // Too large to be allocated, while 32-bit ids may legitimately be.
+        let file = format!("p sp {} 0\n", crate::Idx::MAX);
+        assert!(GraphBuf::read_dimacs(file.as_bytes()).is_err());
+    }
+}
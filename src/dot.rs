@@ -0,0 +1,75 @@
+//! Export of partitions to Graphviz.
+
+use crate::{Graph, Idx};
+use std::io::{self, BufWriter, Write};
+
+/// The colors of the blocks, repeated when there are more blocks.
+const PALETTE: [&str; 10] = [
+    "#1f77b4", "#ff7f0e", "#2ca02c", "#d62728", "#9467bd", "#8c564b", "#e377c2", "#7f7f7f",
+    "#bcbd22", "#17becf",
+];
+
+/// Writes a partition of `graph` as an undirected graph in the DOT format of
+/// Graphviz.
+///
+/// Vertices are labeled with their id and block, and filled with the color of
+/// their block, which only depends on the block id so that renders are
+/// reproducible. Edges between different blocks are dashed and red.
+///
+/// # Panics
+///
+/// This function panics if the length of `part` is different than the number
+/// of vertices.
+pub fn write_partition_dot<W: Write>(graph: &Graph, part: &[Idx], writer: W) -> io::Result<()> {
+    assert_eq!(part.len(), graph.xadj.len() - 1);
+    let mut writer = BufWriter::new(writer);
+
+    writeln!(writer, "graph {{")?;
+    writeln!(writer, "    node [style=filled];")?;
+    for (v, &p) in part.iter().enumerate() {
+        let color = PALETTE[p as usize % PALETTE.len()];
+        writeln!(
+            writer,
+            "    {v} [fillcolor=\"{color}\", label=\"{v}\\n{p}\"];"
+        )?;
+    }
+    for (u, w) in graph.xadj.windows(2).enumerate() {
+        for &v in &graph.adjncy[w[0] as usize..w[1] as usize] {
+            // Each edge is stored in both directions.
+            if (u as Idx) > v {
+                continue;
+            }
+            if part[u] == part[v as usize] {
+                writeln!(writer, "    {u} -- {v};")?;
+            } else {
+                writeln!(writer, "    {u} -- {v} [style=dashed, color=red];")?;
+            }
+        }
+    }
+    writeln!(writer, "}}")?;
+    writer.flush()
+}
+
+#[cfg(test)]
+mod tests {
+
+    use crate::{write_partition_dot, Graph};
+
+    #[test]
+    fn partition_dot() {
+        let mut xadj = vec![0, 2, 5, 7, 9, 12];
+        let mut adjncy = vec![1, 4, 0, 2, 4, 1, 3, 2, 4, 0, 1, 3];
+        let graph = Graph::new(&mut xadj, &mut adjncy);
+
+        let mut dot = Vec::new();
+        write_partition_dot(&graph, &[0, 0, 1, 1, 0], &mut dot).unwrap();
+        let dot = String::from_utf8(dot).unwrap();
+
+        assert!(dot.starts_with("graph {\n"));
+        assert!(dot.ends_with("}\n"));
+        assert_eq!(dot.lines().filter(|l| l.contains(" -- ")).count(), 6);
+        assert_eq!(dot.lines().filter(|l| l.contains("dashed")).count(), 2);
+        assert!(dot.contains("    0 [fillcolor=\"#1f77b4\", label=\"0\\n0\"];\n"));
+        assert!(dot.contains("    2 [fillcolor=\"#ff7f0e\", label=\"2\\n1\"];\n"));
+    }
+}
@@ -0,0 +1,274 @@
+//! Errors reported by this crate.
+
+use crate::Idx;
+use alloc::string::String;
+use core::fmt;
+
+/// An error raised when building a [`Graph`](crate::Graph) or a
+/// [`GraphBuf`](crate::GraphBuf).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GraphError {
+    /// `xadj` is empty, while it must hold at least one element.
+    EmptyXadj,
+
+    /// The length of `adjncy` is different than the last element of `xadj`.
+    AdjncyLengthMismatch { expected: usize, got: usize },
+
+    /// The adjacency matrix has a different number of rows and columns.
+    NotSquare { rows: usize, cols: usize },
+
+    /// The adjacency matrix has different entries at `(row, col)` and
+    /// `(col, row)`.
+    NotSymmetric { row: usize, col: usize },
+
+    /// The number of vertex weights is different than the number of
+    /// vertices.
+    VwgtLengthMismatch { expected: usize, got: usize },
+
+    /// The lengths of `adjwgt` and `adjncy` are different.
+    AdjwgtLengthMismatch { expected: usize, got: usize },
+
+    /// The adjacency structure failed [`Graph::validate`](crate::Graph::validate).
+    Invalid(ValidationError),
+
+    /// An index or a weight does not fit in the index type it is converted
+    /// to.
+    IndexOverflow { value: i64 },
+
+    /// The edge at position `edge` of an edge list refers to `vertex`, which
+    /// is not in `0..n_vertices`.
+    EdgeOutOfRange {
+        edge: usize,
+        vertex: Idx,
+        n_vertices: usize,
+    },
+}
+
+impl fmt::Display for GraphError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GraphError::EmptyXadj => write!(f, "xadj is empty"),
+            GraphError::AdjncyLengthMismatch { expected, got } => write!(
+                f,
+                "adjncy has {got} elements, but the last element of xadj is {expected}"
+            ),
+            GraphError::NotSquare { rows, cols } => {
+                write!(
+                    f,
+                    "the adjacency matrix is {rows}x{cols}, expected a square matrix"
+                )
+            }
+            GraphError::NotSymmetric { row, col } => write!(
+                f,
+                "the adjacency matrix has different entries at ({row}, {col}) and ({col}, {row})"
+            ),
+            GraphError::VwgtLengthMismatch { expected, got } => write!(
+                f,
+                "vwgt has {got} elements, but there are {expected} vertices"
+            ),
+            GraphError::AdjwgtLengthMismatch { expected, got } => {
+                write!(f, "adjwgt has {got} elements, but adjncy has {expected}")
+            }
+            GraphError::Invalid(error) => error.fmt(f),
+            GraphError::IndexOverflow { value } => {
+                write!(f, "{value} does not fit in the target index type")
+            }
+            GraphError::EdgeOutOfRange {
+                edge,
+                vertex,
+                n_vertices,
+            } => write!(
+                f,
+                "edge {edge} refers to vertex {vertex}, but there are {n_vertices} vertices"
+            ),
+        }
+    }
+}
+
+impl core::error::Error for GraphError {
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        match self {
+            GraphError::Invalid(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<ValidationError> for GraphError {
+    fn from(error: ValidationError) -> GraphError {
+        GraphError::Invalid(error)
+    }
+}
+
+/// A structural error found by [`Graph::validate`](crate::Graph::validate).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ValidationError {
+    /// The first element of `xadj` is not zero.
+    NonZeroFirstXadj { value: Idx },
+
+    /// `xadj[index]` is smaller than `xadj[index - 1]`.
+    DecreasingXadj { index: usize, value: Idx },
+
+    /// `adjncy[index]` is not the id of a vertex.
+    NeighborOutOfRange { index: usize, value: Idx },
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ValidationError::NonZeroFirstXadj { value } => {
+                write!(f, "xadj[0] is {value}, expected 0")
+            }
+            ValidationError::DecreasingXadj { index, value } => write!(
+                f,
+                "xadj[{index}] is {value}, which is smaller than the previous element"
+            ),
+            ValidationError::NeighborOutOfRange { index, value } => {
+                write!(f, "adjncy[{index}] is {value}, which is not a vertex")
+            }
+        }
+    }
+}
+
+impl core::error::Error for ValidationError {}
+
+/// An error raised when the arguments of a partition are out of range.
+#[derive(Debug, Clone, PartialEq)]
+pub enum PartitionError {
+    /// The imbalance is negative or not finite.
+    InvalidImbalance { imbalance: f64 },
+
+    /// The number of blocks is not between 1 and the number of vertices.
+    InvalidNParts { n_parts: Idx, n_vertices: Idx },
+
+    /// The number of target weights is different than the number of blocks.
+    TargetWeightsLengthMismatch { expected: usize, got: usize },
+
+    /// The target weight of a block is not positive.
+    NonPositiveTargetWeight { block: usize, value: f64 },
+
+    /// The target weights do not sum to 1.
+    TargetWeightsSum { sum: f64 },
+
+    /// The vertices have several weights, which KaHIP does not support.
+    MultiConstraintUnsupported { ncon: usize },
+
+    /// A vertex is fixed to a block that does not exist.
+    FixedBlockOutOfRange { vertex: usize, block: Idx },
+
+    /// Vertices are fixed while the blocks have different target weights,
+    /// which cannot be combined.
+    FixedWithTargetWeights,
+
+    /// A length is too large to be represented by an [`Idx`].
+    IndexOverflow { len: usize },
+
+    /// The number of blocks of a recursive bisection is not a power of two.
+    NotPowerOfTwo { n_parts: Idx },
+
+    /// The hierarchy and the distances of a process mapping have different
+    /// lengths.
+    HierarchyLengthMismatch { hierarchy: usize, distance: usize },
+
+    /// The levels of the hierarchy of a process mapping are not positive or
+    /// do not multiply to the number of blocks.
+    InvalidHierarchy { n_parts: Idx },
+
+    /// The output buffer has not one element per vertex.
+    PartLengthMismatch { expected: usize, got: usize },
+
+    /// A vertex is in a block that does not exist.
+    BlockOutOfRange { vertex: usize, block: Idx },
+}
+
+impl fmt::Display for PartitionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PartitionError::InvalidImbalance { imbalance } => {
+                write!(
+                    f,
+                    "imbalance is {imbalance}, expected a finite non-negative number"
+                )
+            }
+            PartitionError::InvalidNParts {
+                n_parts,
+                n_vertices,
+            } => write!(
+                f,
+                "n_parts is {n_parts}, expected a number between 1 and {n_vertices}"
+            ),
+            PartitionError::TargetWeightsLengthMismatch { expected, got } => write!(
+                f,
+                "{got} target weights are given, but there are {expected} blocks"
+            ),
+            PartitionError::NonPositiveTargetWeight { block, value } => write!(
+                f,
+                "the target weight of block {block} is {value}, expected a positive number"
+            ),
+            PartitionError::TargetWeightsSum { sum } => {
+                write!(f, "the target weights sum to {sum}, expected 1")
+            }
+            PartitionError::MultiConstraintUnsupported { ncon } => write!(
+                f,
+                "the vertices have {ncon} weights, but KaHIP only supports one"
+            ),
+            PartitionError::FixedBlockOutOfRange { vertex, block } => {
+                write!(
+                    f,
+                    "vertex {vertex} is fixed to block {block}, which does not exist"
+                )
+            }
+            PartitionError::FixedWithTargetWeights => write!(
+                f,
+                "vertices cannot be fixed when blocks have different target weights"
+            ),
+            PartitionError::IndexOverflow { len } => {
+                write!(f, "{len} does not fit in the index type of KaHIP")
+            }
+            PartitionError::NotPowerOfTwo { n_parts } => {
+                write!(f, "n_parts is {n_parts}, expected a power of two")
+            }
+            PartitionError::HierarchyLengthMismatch {
+                hierarchy,
+                distance,
+            } => write!(
+                f,
+                "the hierarchy has {hierarchy} levels, but {distance} distances are given"
+            ),
+            PartitionError::InvalidHierarchy { n_parts } => write!(
+                f,
+                "the levels of the hierarchy must be positive and multiply to n_parts = {n_parts}"
+            ),
+            PartitionError::PartLengthMismatch { expected, got } => write!(
+                f,
+                "the output buffer has {got} elements, but there are {expected} vertices"
+            ),
+            PartitionError::BlockOutOfRange { vertex, block } => {
+                write!(
+                    f,
+                    "vertex {vertex} is in block {block}, which does not exist"
+                )
+            }
+        }
+    }
+}
+
+impl core::error::Error for PartitionError {}
+
+/// An error raised when parsing a [`Mode`](crate::Mode) from a string.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseModeError {
+    pub(crate) input: String,
+}
+
+impl fmt::Display for ParseModeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "unknown mode {:?}, expected one of fast, eco, strong, fastsocial, ecosocial, strongsocial",
+            self.input
+        )
+    }
+}
+
+impl core::error::Error for ParseModeError {}
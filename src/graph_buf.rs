@@ -0,0 +1,801 @@
+//! Owned counterpart of [`Graph`].
+
+use crate::{Graph, GraphError, Idx};
+use std::collections::BTreeMap;
+
+/// How [`GraphBuf::symmetrize`] merges the weights of the edges between the
+/// same two vertices.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WeightMerge {
+    /// The weights are added, e.g. `u -> v` of weight 2 and `v -> u` of
+    /// weight 3 give an edge of weight 5.
+    Sum,
+
+    /// The largest weight is kept, e.g. `u -> v` of weight 2 and `v -> u` of
+    /// weight 3 give an edge of weight 3.
+    Max,
+}
+
+/// A graph that owns its adjacency structure and weights.
+///
+/// Unlike [`Graph`], which borrows its arrays, a [`GraphBuf`] can be stored
+/// and partitioned several times through [`GraphBuf::as_graph`].
+///
+/// With the `serde` feature, deserialized graphs are checked the same way as
+/// [`GraphBuf::new_weighted`] and [`Graph::validate`], since KaHIP would read
+/// out of bounds of inconsistent arrays.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "RawGraphBuf"))]
+pub struct GraphBuf {
+    pub(crate) xadj: Vec<Idx>,
+    pub(crate) adjncy: Vec<Idx>,
+    pub(crate) vwgt: Option<Vec<Idx>>,
+    pub(crate) ncon: usize,
+    pub(crate) adjwgt: Option<Vec<Idx>>,
+}
+
+/// A [`GraphBuf`] as deserialized, before its arrays are checked.
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+struct RawGraphBuf {
+    xadj: Vec<Idx>,
+    adjncy: Vec<Idx>,
+    vwgt: Option<Vec<Idx>>,
+    ncon: usize,
+    adjwgt: Option<Vec<Idx>>,
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<RawGraphBuf> for GraphBuf {
+    type Error = GraphError;
+
+    fn try_from(raw: RawGraphBuf) -> Result<GraphBuf, GraphError> {
+        let mut graph = GraphBuf::new_weighted(raw.xadj, raw.adjncy, None, raw.adjwgt)?;
+        crate::validate_csr(&graph.xadj, &graph.adjncy)?;
+        if let Some(vwgt) = raw.vwgt {
+            let expected = raw.ncon.max(1) * (graph.xadj.len() - 1);
+            if vwgt.len() != expected {
+                return Err(GraphError::VwgtLengthMismatch {
+                    expected,
+                    got: vwgt.len(),
+                });
+            }
+            graph.vwgt = Some(vwgt);
+            graph.ncon = raw.ncon.max(1);
+        }
+        Ok(graph)
+    }
+}
+
+impl GraphBuf {
+    /// Creates a new [`GraphBuf`] from its adjacency structure.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if:
+    /// - `xadj` is empty, or
+    /// - the length of `adjncy` is different than the last element of `xadj`.
+    pub fn new(xadj: Vec<Idx>, adjncy: Vec<Idx>) -> GraphBuf {
+        assert_ne!(xadj.len(), 0);
+        assert_eq!(adjncy.len(), *xadj.last().unwrap() as usize);
+
+        GraphBuf {
+            xadj,
+            adjncy,
+            vwgt: None,
+            ncon: 1,
+            adjwgt: None,
+        }
+    }
+
+    /// Creates a new [`GraphBuf`] from its adjacency structure and optional
+    /// weights, or returns an error if their lengths are inconsistent.
+    ///
+    /// `xadj` must not be empty, `adjncy` must have as many elements as the
+    /// last element of `xadj`, `vwgt` one per vertex, and `adjwgt` one per
+    /// element of `adjncy`.
+    pub fn new_weighted(
+        xadj: Vec<Idx>,
+        adjncy: Vec<Idx>,
+        vwgt: Option<Vec<Idx>>,
+        adjwgt: Option<Vec<Idx>>,
+    ) -> Result<GraphBuf, GraphError> {
+        let expected = match xadj.last() {
+            Some(&last) => last as usize,
+            None => return Err(GraphError::EmptyXadj),
+        };
+        if adjncy.len() != expected {
+            return Err(GraphError::AdjncyLengthMismatch {
+                expected,
+                got: adjncy.len(),
+            });
+        }
+        if let Some(vwgt) = &vwgt {
+            if vwgt.len() != xadj.len() - 1 {
+                return Err(GraphError::VwgtLengthMismatch {
+                    expected: xadj.len() - 1,
+                    got: vwgt.len(),
+                });
+            }
+        }
+        if let Some(adjwgt) = &adjwgt {
+            if adjwgt.len() != adjncy.len() {
+                return Err(GraphError::AdjwgtLengthMismatch {
+                    expected: adjncy.len(),
+                    got: adjwgt.len(),
+                });
+            }
+        }
+
+        Ok(GraphBuf {
+            xadj,
+            adjncy,
+            vwgt,
+            ncon: 1,
+            adjwgt,
+        })
+    }
+
+    /// Creates a new [`GraphBuf`] from the list of neighbors of each vertex:
+    /// the neighbors of vertex `v` are `adj[v]`.
+    ///
+    /// The lists are taken as is, in the same order, so they must already be
+    /// symmetric for KaHIP: `v` in `adj[u]` requires `u` in `adj[v]`. Call
+    /// [`GraphBuf::symmetrize`] on the result when they may not be, e.g. for
+    /// lists of outgoing edges.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if a neighbor is not in `0..adj.len()`.
+    pub fn from_adjacency_list(adj: &[Vec<Idx>]) -> GraphBuf {
+        let n_vertices = adj.len() as Idx;
+        let mut xadj = Vec::with_capacity(adj.len() + 1);
+        let mut adjncy = Vec::with_capacity(adj.iter().map(Vec::len).sum());
+        xadj.push(0);
+        for (v, neighbors) in adj.iter().enumerate() {
+            for &u in neighbors {
+                assert!(
+                    (0..n_vertices).contains(&u),
+                    "neighbor {u} of vertex {v} is out of range, the graph has {n_vertices} vertices"
+                );
+            }
+            adjncy.extend_from_slice(neighbors);
+            xadj.push(adjncy.len() as Idx);
+        }
+        GraphBuf::new(xadj, adjncy)
+    }
+
+    /// Creates a new [`GraphBuf`] with `n_vertices` vertices from a list of
+    /// undirected edges.
+    ///
+    /// Each edge is stored in both directions. Edges are taken as is, so
+    /// duplicates and self-loops are kept.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if an edge refers to a vertex outside of
+    /// `0..n_vertices`.
+    #[deprecated(note = "use `from_edge_list`, which merges parallel edges and drops self-loops")]
+    pub fn from_edges(n_vertices: usize, edges: &[(Idx, Idx)]) -> GraphBuf {
+        let mut xadj = vec![0; n_vertices + 1];
+        for &(u, v) in edges {
+            assert!((0..n_vertices as Idx).contains(&u));
+            assert!((0..n_vertices as Idx).contains(&v));
+            xadj[u as usize + 1] += 1;
+            xadj[v as usize + 1] += 1;
+        }
+        for v in 0..n_vertices {
+            xadj[v + 1] += xadj[v];
+        }
+
+        let mut next: Vec<Idx> = xadj[..n_vertices].to_vec();
+        let mut adjncy = vec![0; xadj[n_vertices] as usize];
+        for &(u, v) in edges {
+            adjncy[next[u as usize] as usize] = v;
+            next[u as usize] += 1;
+            adjncy[next[v as usize] as usize] = u;
+            next[v as usize] += 1;
+        }
+
+        GraphBuf::new(xadj, adjncy)
+    }
+
+    /// Creates a new [`GraphBuf`] with `n_vertices` vertices from a list of
+    /// undirected edges, normalizing it for KaHIP.
+    ///
+    /// Parallel edges are merged, self-loops are dropped, and the neighbors of
+    /// each vertex are sorted. `(u, v)` and `(v, u)` are the same edge.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if an edge refers to a vertex outside of
+    /// `0..n_vertices`.
+    pub fn from_edge_list(n_vertices: usize, edges: &[(Idx, Idx)]) -> GraphBuf {
+        GraphBuf::from_edges_iter(n_vertices, edges.iter().copied())
+    }
+
+    /// Same as [`GraphBuf::from_edge_list`], with the edges taken from an
+    /// iterator, e.g. to read them from a file or a generator without
+    /// collecting them first.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if an edge refers to a vertex outside of
+    /// `0..n_vertices`.
+    pub fn from_edges_iter<I>(n_vertices: usize, edges: I) -> GraphBuf
+    where
+        I: IntoIterator<Item = (Idx, Idx)>,
+    {
+        let edges = edges.into_iter().map(|(u, v)| (u, v, 1));
+        let mut graph = GraphBuf::from_weighted_pairs(n_vertices, edges);
+        graph.adjwgt = None;
+        graph
+    }
+
+    /// Same as [`GraphBuf::from_edge_list`], with the weight of each edge.
+    ///
+    /// The weights of parallel edges are summed.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if:
+    /// - an edge refers to a vertex outside of `0..n_vertices`, or
+    /// - `weights` and `edges` have different lengths.
+    pub fn from_weighted_edge_list(
+        n_vertices: usize,
+        edges: &[(Idx, Idx)],
+        weights: &[Idx],
+    ) -> GraphBuf {
+        assert_eq!(edges.len(), weights.len());
+        let edges = edges.iter().zip(weights).map(|(&(u, v), &w)| (u, v, w));
+        GraphBuf::from_weighted_pairs(n_vertices, edges)
+    }
+
+    /// Builds a sorted, symmetric, loop-free CSR from weighted undirected
+    /// edges, summing the weights of parallel edges.
+    pub(crate) fn from_weighted_pairs(
+        n_vertices: usize,
+        edges: impl Iterator<Item = (Idx, Idx, Idx)>,
+    ) -> GraphBuf {
+        let mut directed = Vec::new();
+        for (u, v, w) in edges {
+            assert!((0..n_vertices as Idx).contains(&u));
+            assert!((0..n_vertices as Idx).contains(&v));
+            if u != v {
+                directed.push((u, v, w));
+                directed.push((v, u, w));
+            }
+        }
+        directed.sort_unstable_by_key(|&(u, v, _)| (u, v));
+
+        let mut xadj = vec![0; n_vertices + 1];
+        let mut adjncy: Vec<Idx> = Vec::with_capacity(directed.len());
+        let mut adjwgt: Vec<Idx> = Vec::with_capacity(directed.len());
+        let mut last = None;
+        for (u, v, w) in directed {
+            if last == Some((u, v)) {
+                *adjwgt.last_mut().unwrap() += w;
+                continue;
+            }
+            last = Some((u, v));
+            xadj[u as usize + 1] += 1;
+            adjncy.push(v);
+            adjwgt.push(w);
+        }
+        for v in 0..n_vertices {
+            xadj[v + 1] += xadj[v];
+        }
+
+        GraphBuf::new(xadj, adjncy).set_adjwgt(adjwgt)
+    }
+
+    /// Returns the undirected counterpart of this graph, read as a directed
+    /// graph where `adjncy[xadj[u]..xadj[u + 1]]` are the targets of the
+    /// edges leaving `u`.
+    ///
+    /// For each edge `u -> v`, the result has both `u -> v` and `v -> u`. The
+    /// weights of all the edges between `u` and `v`, in either direction,
+    /// are merged according to `merge`. When the graph has no edge weights,
+    /// neither does the result and `merge` makes no difference. Self-loops
+    /// are dropped, the neighbors of each vertex are sorted, and the vertex
+    /// weights are kept.
+    pub fn symmetrize(&self, merge: WeightMerge) -> GraphBuf {
+        let mut edges = BTreeMap::new();
+        for (u, w) in self.xadj.windows(2).enumerate() {
+            for k in w[0] as usize..w[1] as usize {
+                let (u, v) = (u as Idx, self.adjncy[k]);
+                if u == v {
+                    continue;
+                }
+                let weight = self.adjwgt.as_ref().map_or(1, |adjwgt| adjwgt[k]);
+                edges
+                    .entry((u.min(v), u.max(v)))
+                    .and_modify(|merged: &mut Idx| {
+                        *merged = match merge {
+                            WeightMerge::Sum => *merged + weight,
+                            WeightMerge::Max => (*merged).max(weight),
+                        }
+                    })
+                    .or_insert(weight);
+            }
+        }
+
+        let n_vertices = self.xadj.len() - 1;
+        let edges = edges.into_iter().map(|((u, v), w)| (u, v, w));
+        let mut graph = GraphBuf::from_weighted_pairs(n_vertices, edges);
+        if self.adjwgt.is_none() {
+            graph.adjwgt = None;
+        }
+        graph.vwgt.clone_from(&self.vwgt);
+        graph.ncon = self.ncon;
+        graph
+    }
+
+    /// Returns this graph without its isolated vertices, along with the new
+    /// id of each vertex of this graph.
+    ///
+    /// Vertices keep their relative order, and vertex `v` of this graph is
+    /// vertex `ids[v]` of the compacted graph: a partition `part` of the
+    /// compacted graph puts vertex `v` in block `part[ids[v]]`. A vertex is
+    /// isolated when no edge starts or ends at it, and its new id is
+    /// `usize::MAX`. Vertex and edge weights follow their vertices and edges.
+    pub fn compact(&self) -> (GraphBuf, Vec<usize>) {
+        let n_vertices = self.xadj.len() - 1;
+        let mut connected: Vec<bool> = self.xadj.windows(2).map(|w| w[0] < w[1]).collect();
+        for &u in &self.adjncy {
+            connected[u as usize] = true;
+        }
+
+        let kept: Vec<usize> = (0..n_vertices).filter(|&v| connected[v]).collect();
+        let mut ids = vec![usize::MAX; n_vertices];
+        for (i, &v) in kept.iter().enumerate() {
+            ids[v] = i;
+        }
+
+        let mut xadj = Vec::with_capacity(kept.len() + 1);
+        xadj.push(0);
+        xadj.extend(kept.iter().map(|&v| self.xadj[v + 1]));
+        let adjncy = self
+            .adjncy
+            .iter()
+            .map(|&u| ids[u as usize] as Idx)
+            .collect();
+        let vwgt = self.vwgt.as_ref().map(|vwgt| {
+            kept.iter()
+                .flat_map(|&v| &vwgt[v * self.ncon..(v + 1) * self.ncon])
+                .copied()
+                .collect()
+        });
+
+        let graph = GraphBuf {
+            xadj,
+            adjncy,
+            vwgt,
+            ncon: self.ncon,
+            adjwgt: self.adjwgt.clone(),
+        };
+        (graph, ids)
+    }
+
+    /// Sorts the neighbors of each vertex by increasing id, moving the edge
+    /// weights along with their edges.
+    ///
+    /// The sort is stable, so duplicate edges keep their relative order.
+    pub fn sort_adjacency(&mut self) {
+        for v in 0..self.xadj.len() - 1 {
+            let range = self.xadj[v] as usize..self.xadj[v + 1] as usize;
+            match &mut self.adjwgt {
+                Some(adjwgt) => {
+                    let mut edges: Vec<(Idx, Idx)> = self.adjncy[range.clone()]
+                        .iter()
+                        .copied()
+                        .zip(adjwgt[range.clone()].iter().copied())
+                        .collect();
+                    edges.sort_by_key(|&(u, _)| u);
+                    for (k, (u, w)) in range.zip(edges) {
+                        self.adjncy[k] = u;
+                        adjwgt[k] = w;
+                    }
+                }
+                None => self.adjncy[range].sort(),
+            }
+        }
+    }
+
+    /// Sets the computational weights of the vertices.
+    ///
+    /// By default all vertices have the same weight.
+    pub fn set_vwgt(mut self, vwgt: Vec<Idx>) -> GraphBuf {
+        assert_eq!(vwgt.len(), self.xadj.len() - 1);
+        self.vwgt = Some(vwgt);
+        self.ncon = 1;
+        self
+    }
+
+    /// Sets the weights of the edges.
+    ///
+    /// By default all edges have the same weight.
+    pub fn set_adjwgt(mut self, adjwgt: Vec<Idx>) -> GraphBuf {
+        assert_eq!(adjwgt.len(), self.adjncy.len());
+        self.adjwgt = Some(adjwgt);
+        self
+    }
+
+    /// Gives every edge the weight `weight`.
+    ///
+    /// This is not the same as leaving the edge weights unset, which KaHIP
+    /// treats as all edges having a weight of 1: with a weight of 2, the edge
+    /// cut is twice as large. KaHIP has no notion of a default edge weight,
+    /// so the weights are stored in full.
+    pub fn with_uniform_edge_weight(self, weight: Idx) -> GraphBuf {
+        let adjwgt = vec![weight; self.adjncy.len()];
+        self.set_adjwgt(adjwgt)
+    }
+
+    /// The adjency structure of the graph (part 1).
+    pub fn xadj(&self) -> &[Idx] {
+        &self.xadj
+    }
+
+    /// The adjency structure of the graph (part 2).
+    pub fn adjncy(&self) -> &[Idx] {
+        &self.adjncy
+    }
+
+    /// The computational weights of the vertices, if any.
+    pub fn vwgt(&self) -> Option<&[Idx]> {
+        self.vwgt.as_deref()
+    }
+
+    /// The weights of the edges, if any.
+    pub fn adjwgt(&self) -> Option<&[Idx]> {
+        self.adjwgt.as_deref()
+    }
+
+    /// Borrows this graph as a [`Graph`], e.g. to partition it.
+    pub fn as_graph(&mut self) -> Graph<'_> {
+        Graph {
+            xadj: &mut self.xadj,
+            adjncy: &mut self.adjncy,
+            vwgt: self.vwgt.as_deref_mut(),
+            ncon: self.ncon,
+            fixed: None,
+            adjwgt: self.adjwgt.as_deref_mut(),
+        }
+    }
+}
+
+/// Builds a graph from its number of vertices and its undirected edges, as
+/// [`GraphBuf::from_edge_list`] does, returning an error if an edge refers to
+/// a vertex outside of `0..n_vertices`.
+impl TryFrom<(usize, &[(Idx, Idx)])> for GraphBuf {
+    type Error = GraphError;
+
+    fn try_from((n_vertices, edges): (usize, &[(Idx, Idx)])) -> Result<GraphBuf, GraphError> {
+        for (edge, &(u, v)) in edges.iter().enumerate() {
+            if let Some(vertex) = [u, v]
+                .into_iter()
+                .find(|&w| w < 0 || w as usize >= n_vertices)
+            {
+                return Err(GraphError::EdgeOutOfRange {
+                    edge,
+                    vertex,
+                    n_vertices,
+                });
+            }
+        }
+        Ok(GraphBuf::from_edge_list(n_vertices, edges))
+    }
+}
+
+/// Builds a graph from the list of neighbors of each vertex, as
+/// [`GraphBuf::from_adjacency_list`] does, which panics if a neighbor is out
+/// of range.
+impl From<Vec<Vec<Idx>>> for GraphBuf {
+    fn from(adj: Vec<Vec<Idx>>) -> GraphBuf {
+        GraphBuf::from_adjacency_list(&adj)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use crate::{edge_cut, GraphBuf, GraphError, Idx, Mode, WeightMerge};
+
+    #[test]
+    fn with_uniform_edge_weight() {
+        let graph = GraphBuf::new(
+            vec![0, 2, 5, 7, 9, 12],
+            vec![1, 4, 0, 2, 4, 1, 3, 2, 4, 0, 1, 3],
+        );
+        let part = [0, 0, 1, 1, 0];
+
+        let weighted = graph.clone().with_uniform_edge_weight(2);
+        assert_eq!(weighted.adjwgt(), Some(&[2; 12][..]));
+        assert_eq!(edge_cut(&weighted, &part), 2 * edge_cut(&graph, &part));
+    }
+
+    #[test]
+    fn compact() {
+        // Vertices 1 and 4 are isolated.
+        let graph = GraphBuf::from_weighted_edge_list(6, &[(0, 2), (2, 3), (3, 5)], &[7, 8, 9])
+            .set_vwgt(vec![1, 2, 3, 4, 5, 6]);
+
+        let (compacted, ids) = graph.compact();
+        assert_eq!(ids, [0, usize::MAX, 1, 2, usize::MAX, 3]);
+        assert_eq!(compacted.xadj(), [0, 1, 3, 5, 6]);
+        assert_eq!(compacted.adjncy(), [1, 0, 2, 1, 3, 2]);
+        assert_eq!(compacted.adjwgt(), Some(&[7, 7, 8, 8, 9, 9][..]));
+        assert_eq!(compacted.vwgt(), Some(&[1, 3, 4, 6][..]));
+
+        // Translates a partition of the compacted graph back.
+        let part = [0, 0, 1, 1];
+        let original: Vec<Option<Idx>> = ids.iter().map(|&i| part.get(i).copied()).collect();
+        assert_eq!(original, [Some(0), None, Some(0), Some(1), None, Some(1)]);
+
+        let single = GraphBuf::from_edge_list(3, &[(0, 2)]);
+        let (compacted, ids) = single.compact();
+        assert_eq!(compacted.xadj().len(), single.xadj().len() - 1);
+        assert_eq!(ids, [0, usize::MAX, 1]);
+        assert_eq!(compacted, GraphBuf::from_edge_list(2, &[(0, 1)]));
+    }
+
+    #[test]
+    fn sort_adjacency() {
+        let mut graph = GraphBuf::new(
+            vec![0, 2, 5, 7, 9, 12],
+            vec![4, 1, 4, 2, 0, 3, 1, 2, 4, 3, 1, 0],
+        )
+        .set_adjwgt(vec![2, 1, 4, 3, 1, 5, 3, 5, 6, 6, 4, 2]);
+
+        graph.sort_adjacency();
+        assert_eq!(graph.adjncy(), [1, 4, 0, 2, 4, 1, 3, 2, 4, 0, 1, 3]);
+        assert_eq!(
+            graph.adjwgt(),
+            Some(&[1, 2, 1, 3, 4, 3, 5, 5, 6, 2, 4, 6][..])
+        );
+
+        let mut unweighted = GraphBuf::new(vec![0, 3], vec![2, 0, 1]);
+        unweighted.sort_adjacency();
+        assert_eq!(unweighted.adjncy(), [0, 1, 2]);
+    }
+
+    #[test]
+    fn new_weighted() {
+        let xadj = vec![0, 2, 5, 7, 9, 12];
+        let adjncy = vec![1, 4, 0, 2, 4, 1, 3, 2, 4, 0, 1, 3];
+        let vwgt = vec![1, 2, 3, 4, 5];
+        let adjwgt = vec![1, 2, 1, 3, 4, 3, 5, 5, 6, 2, 4, 6];
+
+        let graph = GraphBuf::new_weighted(
+            xadj.clone(),
+            adjncy.clone(),
+            Some(vwgt.clone()),
+            Some(adjwgt.clone()),
+        )
+        .unwrap();
+        assert_eq!(
+            graph,
+            GraphBuf::new(xadj.clone(), adjncy.clone())
+                .set_vwgt(vwgt.clone())
+                .set_adjwgt(adjwgt.clone())
+        );
+
+        let error = GraphBuf::new_weighted(xadj.clone(), adjncy.clone(), Some(vec![1; 4]), None)
+            .unwrap_err();
+        assert_eq!(
+            error,
+            GraphError::VwgtLengthMismatch {
+                expected: 5,
+                got: 4
+            }
+        );
+        assert_eq!(
+            error.to_string(),
+            "vwgt has 4 elements, but there are 5 vertices"
+        );
+
+        let error =
+            GraphBuf::new_weighted(xadj.clone(), adjncy, None, Some(vec![1; 13])).unwrap_err();
+        assert_eq!(
+            error,
+            GraphError::AdjwgtLengthMismatch {
+                expected: 12,
+                got: 13
+            }
+        );
+        assert_eq!(
+            error.to_string(),
+            "adjwgt has 13 elements, but adjncy has 12"
+        );
+
+        assert_eq!(
+            GraphBuf::new_weighted(xadj, vec![], None, None),
+            Err(GraphError::AdjncyLengthMismatch {
+                expected: 12,
+                got: 0
+            })
+        );
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    fn from_edges() {
+        let graph = GraphBuf::from_edges(5, &[(0, 1), (0, 4), (1, 2), (1, 4), (2, 3), (3, 4)]);
+
+        assert_eq!(graph.xadj(), [0, 2, 5, 7, 9, 12]);
+        assert_eq!(graph.adjncy(), [1, 4, 0, 2, 4, 1, 3, 2, 4, 0, 1, 3]);
+    }
+
+    #[test]
+    fn from_adjacency_list() {
+        let adj = vec![
+            vec![1, 4],
+            vec![0, 2, 4],
+            vec![1, 3],
+            vec![2, 4],
+            vec![0, 1, 3],
+        ];
+        let graph = GraphBuf::from_adjacency_list(&adj);
+        assert_eq!(graph.xadj(), [0, 2, 5, 7, 9, 12]);
+        assert_eq!(graph.adjncy(), [1, 4, 0, 2, 4, 1, 3, 2, 4, 0, 1, 3]);
+
+        // Outgoing edges only, made undirected afterwards.
+        let adj = vec![vec![2], vec![], vec![1]];
+        let graph = GraphBuf::from_adjacency_list(&adj).symmetrize(WeightMerge::Max);
+        assert_eq!(graph.xadj(), [0, 1, 2, 4]);
+        assert_eq!(graph.adjncy(), [2, 2, 0, 1]);
+
+        assert_eq!(GraphBuf::from_adjacency_list(&[]).xadj(), [0]);
+    }
+
+    #[test]
+    #[should_panic(expected = "neighbor 3 of vertex 1 is out of range")]
+    fn from_adjacency_list_out_of_range() {
+        GraphBuf::from_adjacency_list(&[vec![1], vec![0, 3]]);
+    }
+
+    #[test]
+    fn conversions() {
+        let sample = GraphBuf::new(
+            vec![0, 2, 5, 7, 9, 12],
+            vec![1, 4, 0, 2, 4, 1, 3, 2, 4, 0, 1, 3],
+        );
+
+        let edges: &[(Idx, Idx)] = &[(0, 1), (0, 4), (1, 2), (1, 4), (2, 3), (3, 4)];
+        assert_eq!(GraphBuf::try_from((5, edges)), Ok(sample.clone()));
+        let error = GraphBuf::try_from((4, edges)).unwrap_err();
+        assert_eq!(
+            error,
+            GraphError::EdgeOutOfRange {
+                edge: 1,
+                vertex: 4,
+                n_vertices: 4
+            }
+        );
+        assert_eq!(
+            error.to_string(),
+            "edge 1 refers to vertex 4, but there are 4 vertices"
+        );
+
+        let adj = vec![
+            vec![1, 4],
+            vec![0, 2, 4],
+            vec![1, 3],
+            vec![2, 4],
+            vec![0, 1, 3],
+        ];
+        let graph: GraphBuf = adj.into();
+        assert_eq!(graph, sample);
+    }
+
+    #[test]
+    fn from_edge_list() {
+        let graph = GraphBuf::from_edge_list(3, &[(1, 2), (0, 1), (2, 0), (1, 0), (1, 1)]);
+
+        assert_eq!(graph.xadj(), [0, 2, 4, 6]);
+        assert_eq!(graph.adjncy(), [1, 2, 0, 2, 0, 1]);
+        assert_eq!(graph.adjwgt(), None);
+    }
+
+    #[test]
+    fn from_edges_iter() {
+        let edges = [(1, 2), (0, 1), (2, 0), (1, 0), (1, 1)];
+        assert_eq!(
+            GraphBuf::from_edges_iter(3, edges),
+            GraphBuf::from_edge_list(3, &edges)
+        );
+
+        // A ring, generated on the fly.
+        let graph = GraphBuf::from_edges_iter(4, (0..4).map(|v| (v, (v + 1) % 4)));
+        assert_eq!(graph.xadj(), [0, 2, 4, 6, 8]);
+        assert_eq!(graph.adjncy(), [1, 3, 0, 2, 1, 3, 0, 2]);
+    }
+
+    #[test]
+    fn from_weighted_edge_list() {
+        let graph =
+            GraphBuf::from_weighted_edge_list(3, &[(1, 2), (0, 1), (2, 0), (1, 0)], &[1, 2, 3, 4]);
+
+        assert_eq!(graph.xadj(), [0, 2, 4, 6]);
+        assert_eq!(graph.adjncy(), [1, 2, 0, 2, 0, 1]);
+        assert_eq!(graph.adjwgt(), Some(&[6, 3, 6, 1, 3, 1][..]));
+    }
+
+    #[test]
+    fn partition() {
+        let mut graph = GraphBuf::new(
+            vec![0, 2, 5, 7, 9, 12],
+            vec![1, 4, 0, 2, 4, 1, 3, 2, 4, 0, 1, 3],
+        )
+        .set_vwgt(vec![1; 5]);
+
+        let first = graph.as_graph().partition(2, 0.03, true, 1234, Mode::Eco);
+        let second = graph.as_graph().partition(2, 0.03, true, 1234, Mode::Eco);
+
+        assert_eq!(first.part.len(), 5);
+        assert_eq!(first, second);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_round_trip() {
+        let unweighted = GraphBuf::new(vec![0, 1, 2], vec![1, 0]);
+        let weighted = GraphBuf::new(
+            vec![0, 2, 5, 7, 9, 12],
+            vec![1, 4, 0, 2, 4, 1, 3, 2, 4, 0, 1, 3],
+        )
+        .set_vwgt(vec![1, 2, 3, 4, 5])
+        .set_adjwgt(vec![1, 2, 1, 3, 4, 3, 5, 5, 6, 2, 4, 6]);
+
+        for graph in [unweighted, weighted] {
+            let json = serde_json::to_string(&graph).unwrap();
+            assert_eq!(serde_json::from_str::<GraphBuf>(&json).unwrap(), graph);
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_rejects_malformed() {
+        let malformed = [
+            // adjncy is shorter than the last element of xadj.
+            r#"{"xadj":[0,2,4],"adjncy":[1,0],"vwgt":null,"ncon":1,"adjwgt":null}"#,
+            // xadj decreases.
+            r#"{"xadj":[0,2,1,2],"adjncy":[1,0],"vwgt":null,"ncon":1,"adjwgt":null}"#,
+            // A neighbor is not a vertex.
+            r#"{"xadj":[0,1,2],"adjncy":[1,5],"vwgt":null,"ncon":1,"adjwgt":null}"#,
+            // Wrong number of weights.
+            r#"{"xadj":[0,1,2],"adjncy":[1,0],"vwgt":[1],"ncon":1,"adjwgt":null}"#,
+            r#"{"xadj":[0,1,2],"adjncy":[1,0],"vwgt":[1,1],"ncon":2,"adjwgt":null}"#,
+            r#"{"xadj":[0,1,2],"adjncy":[1,0],"vwgt":null,"ncon":1,"adjwgt":[1]}"#,
+            r#"{"xadj":[],"adjncy":[],"vwgt":null,"ncon":1,"adjwgt":null}"#,
+        ];
+        for json in malformed {
+            assert!(serde_json::from_str::<GraphBuf>(json).is_err(), "{json}");
+        }
+    }
+
+    #[test]
+    fn symmetrize() {
+        // 0 -> 1, 1 -> 0, 1 -> 2 and 2 -> 0.
+        let directed = GraphBuf::new(vec![0, 1, 3, 4], vec![1, 0, 2, 0])
+            .set_vwgt(vec![1, 2, 3])
+            .set_adjwgt(vec![2, 3, 4, 5]);
+
+        let sum = directed.symmetrize(WeightMerge::Sum);
+        assert_eq!(sum.xadj(), [0, 2, 4, 6]);
+        assert_eq!(sum.adjncy(), [1, 2, 0, 2, 0, 1]);
+        assert_eq!(sum.adjwgt(), Some(&[5, 5, 5, 4, 5, 4][..]));
+        assert_eq!(sum.vwgt(), Some(&[1, 2, 3][..]));
+
+        let max = directed.symmetrize(WeightMerge::Max);
+        assert_eq!(max.adjncy(), [1, 2, 0, 2, 0, 1]);
+        assert_eq!(max.adjwgt(), Some(&[3, 5, 3, 4, 5, 4][..]));
+
+        let unweighted = GraphBuf::new(vec![0, 1, 3, 4], vec![1, 0, 2, 0]);
+        let graph = unweighted.symmetrize(WeightMerge::Sum);
+        assert_eq!(graph.adjncy(), [1, 2, 0, 2, 0, 1]);
+        assert_eq!(graph.adjwgt(), None);
+    }
+}
@@ -0,0 +1,111 @@
+//! Read-only view of a graph.
+
+#[cfg(feature = "std")]
+use crate::{Graph, GraphBuf};
+use crate::{GraphError, Idx};
+
+/// A graph that borrows its adjacency structure and weights immutably.
+///
+/// KaHIP takes every array by mutable pointer, so [`Graph`] holds mutable
+/// borrows of the weights. Analysis does not need them: the metrics take a
+/// [`GraphRef`], which can be built from shared slices, or from a shared
+/// borrow of a [`Graph`] or a [`GraphBuf`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GraphRef<'a> {
+    pub(crate) xadj: &'a [Idx],
+    pub(crate) adjncy: &'a [Idx],
+    pub(crate) vwgt: Option<&'a [Idx]>,
+    pub(crate) ncon: usize,
+    pub(crate) adjwgt: Option<&'a [Idx]>,
+}
+
+impl<'a> GraphRef<'a> {
+    /// Creates a new [`GraphRef`] from its adjacency structure.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if:
+    /// - `xadj` is empty, or
+    /// - the length of `adjncy` is different than the last element of `xadj`.
+    pub fn new(xadj: &'a [Idx], adjncy: &'a [Idx]) -> GraphRef<'a> {
+        GraphRef::try_new(xadj, adjncy).unwrap()
+    }
+
+    /// Same as [`GraphRef::new`], returning an error if the arrays are
+    /// inconsistent.
+    pub fn try_new(xadj: &'a [Idx], adjncy: &'a [Idx]) -> Result<GraphRef<'a>, GraphError> {
+        let expected = match xadj.last() {
+            Some(&last) => last as usize,
+            None => return Err(GraphError::EmptyXadj),
+        };
+        if adjncy.len() != expected {
+            return Err(GraphError::AdjncyLengthMismatch {
+                expected,
+                got: adjncy.len(),
+            });
+        }
+
+        Ok(GraphRef {
+            xadj,
+            adjncy,
+            vwgt: None,
+            ncon: 1,
+            adjwgt: None,
+        })
+    }
+
+    /// Sets the computational weights of the vertices.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if `vwgt` has not one weight per vertex.
+    pub fn set_vwgt(mut self, vwgt: &'a [Idx]) -> GraphRef<'a> {
+        assert_eq!(vwgt.len(), self.xadj.len() - 1);
+        self.vwgt = Some(vwgt);
+        self.ncon = 1;
+        self
+    }
+
+    /// Sets the weights of the edges.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if the lengths of `adjwgt` and `adjncy` are
+    /// different.
+    pub fn set_adjwgt(mut self, adjwgt: &'a [Idx]) -> GraphRef<'a> {
+        assert_eq!(adjwgt.len(), self.adjncy.len());
+        self.adjwgt = Some(adjwgt);
+        self
+    }
+
+    /// The number of vertices.
+    pub fn num_vertices(&self) -> usize {
+        self.xadj.len() - 1
+    }
+}
+
+#[cfg(feature = "std")]
+impl<'a> From<&'a Graph<'_>> for GraphRef<'a> {
+    fn from(graph: &'a Graph<'_>) -> GraphRef<'a> {
+        GraphRef {
+            xadj: graph.xadj,
+            adjncy: graph.adjncy,
+            vwgt: graph.vwgt.as_deref(),
+            ncon: graph.ncon,
+            adjwgt: graph.adjwgt.as_deref(),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<'a> From<&'a GraphBuf> for GraphRef<'a> {
+    fn from(graph: &'a GraphBuf) -> GraphRef<'a> {
+        GraphRef {
+            xadj: &graph.xadj,
+            adjncy: &graph.adjncy,
+            vwgt: graph.vwgt.as_deref(),
+            ncon: graph.ncon,
+            adjwgt: graph.adjwgt.as_deref(),
+        }
+    }
+}
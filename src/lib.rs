@@ -1,9 +1,112 @@
 //! This crate provides a thin but idiomatic API around KaHIP.
+//!
+//! Everything but the [metrics](csr) needs the default `std` feature, which
+//! links KaHIP. Without it, the crate is `no_std` and only depends on
+//! `alloc`, so that partitions computed elsewhere can still be analyzed.
 
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+#[cfg(feature = "std")]
+use core::fmt;
+#[cfg(feature = "std")]
+use core::iter;
+#[cfg(feature = "std")]
 use core::mem;
+#[cfg(feature = "std")]
 use core::ptr;
+#[cfg(feature = "std")]
+use core::str::FromStr;
+#[cfg(feature = "std")]
+use core::time::Duration;
+#[cfg(feature = "std")]
 use kahip_sys as m;
+#[cfg(feature = "std")]
+use std::sync::{Mutex, MutexGuard, PoisonError};
+#[cfg(feature = "std")]
+use std::time::Instant;
+
+#[cfg(all(unix, feature = "std"))]
+mod capture;
+#[cfg(feature = "std")]
+mod config;
+#[cfg(feature = "std")]
+mod contiguity;
+#[cfg(feature = "std")]
+mod dimacs;
+#[cfg(feature = "std")]
+mod dot;
+mod error;
+#[cfg(feature = "std")]
+mod graph_buf;
+mod graph_ref;
+#[cfg(feature = "std")]
+mod metis;
+#[cfg(feature = "metis")]
+mod metis_interop;
+mod metrics;
+#[cfg(feature = "ndarray")]
+mod ndarray_interop;
+#[cfg(feature = "rayon")]
+mod par;
+#[cfg(feature = "mpi")]
+mod parhip;
+#[cfg(feature = "std")]
+mod partition;
+#[cfg(feature = "std")]
+mod partitioner;
+#[cfg(feature = "petgraph")]
+mod petgraph_interop;
+#[cfg(feature = "std")]
+mod refine;
+#[cfg(feature = "sprs")]
+mod sprs_interop;
+#[cfg(feature = "std")]
+mod subgraph;
+#[cfg(all(feature = "std", any(test, feature = "testgraphs")))]
+pub mod testgraphs;
+#[cfg(feature = "std")]
+mod upartition;
+
+#[cfg(feature = "std")]
+pub use self::metis::{read_partition, write_partition};
+#[cfg(feature = "std")]
+pub use config::{Imbalance, PartitionConfig, Seed, TARGET_WEIGHTS_TOLERANCE};
+#[cfg(feature = "std")]
+pub use contiguity::{connected_components, enforce_contiguity};
+#[cfg(feature = "std")]
+pub use dot::write_partition_dot;
+pub use error::{GraphError, ParseModeError, PartitionError, ValidationError};
+#[cfg(feature = "std")]
+pub use graph_buf::{GraphBuf, WeightMerge};
+pub use graph_ref::GraphRef;
+#[cfg(feature = "metis")]
+pub use metis_interop::MetisGraphBuf;
+pub use metrics::csr;
+pub use metrics::{
+    achieved_imbalance, balance, block_sizes, block_weights, boundary_vertices,
+    boundary_vertices_of_block, communication_volume, edge_cut, is_balanced, modularity,
+    per_block_cut, report, PartitionReport,
+};
+#[cfg(feature = "rayon")]
+pub use metrics::{par_communication_volume, par_edge_cut};
+#[cfg(feature = "rayon")]
+pub use par::partition_many;
+#[cfg(feature = "mpi")]
+pub use parhip::{DistGraph, ParIdx, ParMode};
+#[cfg(feature = "std")]
+pub use partition::Partition;
+#[cfg(feature = "std")]
+pub use partitioner::Partitioner;
+#[cfg(feature = "std")]
+pub use subgraph::induced_subgraph;
+#[cfg(feature = "std")]
+pub use upartition::upartition;
 
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Mode {
     Fast = m::FAST as isize,
     Eco = m::ECO as isize,
@@ -13,38 +116,254 @@ pub enum Mode {
     StrongSocial = m::STRONGSOCIAL as isize,
 }
 
-pub type Idx = std::os::raw::c_int;
+#[cfg(feature = "std")]
+/// The trade-off between speed and quality of a KaHIP preconfiguration.
+///
+/// Combined with whether the graph is a social network, or more generally
+/// has a skewed degree distribution, this picks a [`Mode`], see
+/// [`Mode::new`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Quality {
+    Fast,
+    Eco,
+    Strong,
+}
+
+#[cfg(feature = "std")]
+impl Mode {
+    /// Returns the preconfiguration of the given quality, tuned for social
+    /// networks if `social` is set.
+    pub fn new(quality: Quality, social: bool) -> Mode {
+        match (quality, social) {
+            (Quality::Fast, false) => Mode::Fast,
+            (Quality::Eco, false) => Mode::Eco,
+            (Quality::Strong, false) => Mode::Strong,
+            (Quality::Fast, true) => Mode::FastSocial,
+            (Quality::Eco, true) => Mode::EcoSocial,
+            (Quality::Strong, true) => Mode::StrongSocial,
+        }
+    }
+
+    /// The trade-off between speed and quality of this preconfiguration.
+    pub fn quality(self) -> Quality {
+        match self {
+            Mode::Fast | Mode::FastSocial => Quality::Fast,
+            Mode::Eco | Mode::EcoSocial => Quality::Eco,
+            Mode::Strong | Mode::StrongSocial => Quality::Strong,
+        }
+    }
+
+    /// Whether this preconfiguration is tuned for social networks.
+    pub fn is_social(self) -> bool {
+        matches!(
+            self,
+            Mode::FastSocial | Mode::EcoSocial | Mode::StrongSocial
+        )
+    }
+}
+
+#[cfg(feature = "std")]
+/// Writes the name of the mode in lower case, as parsed by [`FromStr`].
+impl fmt::Display for Mode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Mode::Fast => "fast",
+            Mode::Eco => "eco",
+            Mode::Strong => "strong",
+            Mode::FastSocial => "fastsocial",
+            Mode::EcoSocial => "ecosocial",
+            Mode::StrongSocial => "strongsocial",
+        })
+    }
+}
+
+#[cfg(feature = "std")]
+/// Parses the name of a mode, ignoring case, e.g. `eco` or `StrongSocial`.
+impl FromStr for Mode {
+    type Err = ParseModeError;
+
+    fn from_str(s: &str) -> Result<Mode, ParseModeError> {
+        Ok(match s.to_ascii_lowercase().as_str() {
+            "fast" => Mode::Fast,
+            "eco" => Mode::Eco,
+            "strong" => Mode::Strong,
+            "fastsocial" => Mode::FastSocial,
+            "ecosocial" => Mode::EcoSocial,
+            "strongsocial" => Mode::StrongSocial,
+            _ => {
+                return Err(ParseModeError {
+                    input: s.to_string(),
+                })
+            }
+        })
+    }
+}
+
+#[cfg(feature = "std")]
+/// How KaHIP builds the mapping in [`Graph::process_mapping`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MapMode {
+    Multisection = m::MAPMODE_MULTISECTION as isize,
+    Bisection = m::MAPMODE_BISECTION as isize,
+}
+
+/// The integer type used by KaHIP for vertex ids, weights, and counts.
+///
+/// This is a C `int`, or a 64-bit integer when the `idx64` feature is
+/// enabled for use with a KaHIP built with 64-bit indices. The width is not
+/// recorded by the library, so the build of `kahip-sys` checks instead that
+/// KaHIP's header declares 64-bit integers.
+#[cfg(not(feature = "idx64"))]
+pub type Idx = core::ffi::c_int;
+
+/// The integer type used by KaHIP for vertex ids, weights, and counts.
+///
+/// This is a C `int`, or a 64-bit integer when the `idx64` feature is
+/// enabled for use with a KaHIP built with 64-bit indices. The width is not
+/// recorded by the library, so the build of `kahip-sys` checks instead that
+/// KaHIP's header declares 64-bit integers.
+#[cfg(feature = "idx64")]
+pub type Idx = i64;
+
+#[cfg(feature = "std")]
+/// Returns the version of the KaHIP this crate was built against, e.g.
+/// `"3.16"`, or `"unknown"` when it could not be determined at build time.
+pub fn kahip_version() -> &'static str {
+    m::VERSION
+}
+
+#[cfg(feature = "std")]
+/// Sets the number of OpenMP threads KaHIP may use, with the `openmp`
+/// feature, and does nothing otherwise.
+///
+/// This is the same as setting `OMP_NUM_THREADS` in the environment, but only
+/// affects the calling thread, which is the thread KaHIP runs on. The feature
+/// links OpenMP's runtime, so KaHIP must have been built with OpenMP. Without
+/// it, the number of threads is left to the environment.
+///
+/// # Panics
+///
+/// This function panics if `n` is zero or does not fit in a C `int`.
+pub fn set_num_threads(n: usize) {
+    assert!(n > 0, "the number of threads must be positive");
+    let n = std::os::raw::c_int::try_from(n).expect("too many threads");
+    #[cfg(feature = "openmp")]
+    unsafe {
+        m::omp_set_num_threads(n);
+    }
+    #[cfg(not(feature = "openmp"))]
+    let _ = n;
+}
+
+#[cfg(feature = "std")]
+/// Serializes the calls into KaHIP, which keeps global state (its random
+/// number generator, the redirection of its output) and is not reentrant.
+static KAHIP_LOCK: Mutex<()> = Mutex::new(());
+
+#[cfg(feature = "std")]
+/// Locks [`KAHIP_LOCK`] for the duration of a call into KaHIP.
+fn lock_kahip() -> MutexGuard<'static, ()> {
+    // A panic while the lock is held happens on the Rust side, KaHIP's state
+    // is not affected.
+    KAHIP_LOCK.lock().unwrap_or_else(PoisonError::into_inner)
+}
 
+#[cfg(feature = "std")]
 /// Builder structure to setup a graph partition computation.
 ///
 /// This structure holds the required arguments for KaHIP to compute a
 /// partition. It also offers methods to easily set any optional argument.
 ///
-
+/// # Thread safety
+///
+/// `Graph` and [`GraphBuf`] are `Send`, so independent graphs can be handed
+/// to other threads. KaHIP itself is not reentrant: it seeds and draws from a
+/// global random number generator. Calls into KaHIP are therefore serialized
+/// by a global lock, so partitioning from several threads is safe but does
+/// not run in parallel, and each result stays the same as with a
+/// single-threaded call with the same seed.
 #[derive(Debug, PartialEq)]
 pub struct Graph<'a> {
     /// The adjency structure of the graph (part 1).
-    xadj: &'a mut [Idx],
+    xadj: &'a [Idx],
 
     /// The adjency structure of the graph (part 2).
     ///
     /// Required size: xadj.last()
-    adjncy: &'a mut [Idx],
+    adjncy: &'a [Idx],
 
     /// The computational weights of the vertices.
     ///
-    /// Required size: (xadj.len()-1)
+    /// Required size: ncon*(xadj.len()-1)
     vwgt: Option<&'a mut [Idx]>,
 
+    /// The number of weights of each vertex.
+    ncon: usize,
+
+    /// The block each vertex is fixed to, or -1 for free vertices.
+    ///
+    /// Required size: (xadj.len()-1)
+    fixed: Option<&'a [Idx]>,
+
     /// The weight of the edges.
     ///
     /// Required size: xadj.last()
     adjwgt: Option<&'a mut [Idx]>,
 }
 
+#[cfg(feature = "std")]
+/// The outcome of a [`Graph::process_mapping`] call.
+///
+/// The two objectives differ in how they weigh the edges between blocks.
+/// The edge cut counts each of them once, wherever its blocks are mapped.
+/// The communication cost, or quadratic assignment objective, multiplies
+/// the weight of each of them by the distance between the processors of its
+/// two endpoints: an edge between two cores of the same processor costs less
+/// than one between two nodes. The mapping minimizes the latter.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MappingResult {
+    /// The processor of each vertex.
+    pub part: Vec<Idx>,
+
+    /// The total weight of the edges between different processors.
+    pub edge_cut: Idx,
+
+    /// The sum, over the edges between different processors, of their weight
+    /// times the distance between these processors.
+    pub qap: Idx,
+}
+
+#[cfg(feature = "std")]
+/// The outcome of a [`Graph::partition`] call.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PartitionResult {
+    /// The block of each vertex.
+    pub part: Vec<Idx>,
+
+    /// The total weight of the edges between different blocks.
+    pub edge_cut: Idx,
+
+    /// The number of blocks, as left by KaHIP after the call.
+    pub n_parts: Idx,
+
+    /// The imbalance, as left by KaHIP after the call.
+    ///
+    /// With target weights, see [`PartitionConfig::set_target_weights`], this
+    /// is instead the imbalance of the blocks with respect to their targets:
+    /// the largest ratio of the weight of a block to its target weight,
+    /// minus one.
+    pub imbalance: f64,
+}
+
+#[cfg(feature = "std")]
 impl<'a> Graph<'a> {
     /// Creates a new [`Graph`] object to be partitioned.
     ///
+    /// The graph may have no edges, with `xadj` all zeros and an empty
+    /// `adjncy`: its vertices are isolated, and partitions spread them over
+    /// the blocks without calling KaHIP, see [`Graph::try_partition_with`].
+    ///
     /// # Panics
     ///
     /// This function panics if:
@@ -55,41 +374,397 @@ impl<'a> Graph<'a> {
     ///
     /// While nothing should be modified by the [`Graph`] structure, KaHIP
     /// doesn't specify any `const` modifier, so everything must be mutable on
-    /// Rust's side.
+    /// Rust's side. The adjacency structure is known to be read-only though,
+    /// see [`Graph::new_ref`] to pass it as shared slices.
     pub fn new(xadj: &'a mut [Idx], adjncy: &'a mut [Idx]) -> Graph<'a> {
-        assert_ne!(xadj.len(), 0);
-        assert_eq!(adjncy.len(), *xadj.last().unwrap() as usize);
+        Graph::try_new(xadj, adjncy).unwrap()
+    }
+
+    /// Creates a new [`Graph`] object to be partitioned, or returns an error
+    /// if the arrays are inconsistent.
+    ///
+    /// See [`Graph::new`] for the requirements on `xadj` and `adjncy`.
+    pub fn try_new(xadj: &'a mut [Idx], adjncy: &'a mut [Idx]) -> Result<Graph<'a>, GraphError> {
+        Graph::try_new_ref(xadj, adjncy)
+    }
+
+    /// Creates a new [`Graph`] object to be partitioned from shared slices.
+    ///
+    /// KaHIP takes `xadj` and `adjncy` as non-`const` pointers, but only
+    /// reads them: it copies the graph into its own data structure before
+    /// working on it. They are thus cast to mutable pointers when calling
+    /// KaHIP, which never writes through them. Vertex and edge weights are
+    /// still required to be mutable.
+    ///
+    /// # Panics
+    ///
+    /// See [`Graph::new`].
+    pub fn new_ref(xadj: &'a [Idx], adjncy: &'a [Idx]) -> Graph<'a> {
+        Graph::try_new_ref(xadj, adjncy).unwrap()
+    }
 
-        Graph {
+    /// Same as [`Graph::new_ref`], returning an error if the arrays are
+    /// inconsistent.
+    pub fn try_new_ref(xadj: &'a [Idx], adjncy: &'a [Idx]) -> Result<Graph<'a>, GraphError> {
+        let expected = match xadj.last() {
+            Some(&last) => last as usize,
+            None => return Err(GraphError::EmptyXadj),
+        };
+        if adjncy.len() != expected {
+            return Err(GraphError::AdjncyLengthMismatch {
+                expected,
+                got: adjncy.len(),
+            });
+        }
+
+        Ok(Graph {
             xadj,
             adjncy,
             adjwgt: None,
             vwgt: None,
+            ncon: 1,
+            fixed: None,
+        })
+    }
+
+    /// Creates a new [`Graph`] object from shared slices, or returns an error
+    /// if they are not a valid CSR adjacency structure.
+    ///
+    /// On top of the checks of [`Graph::try_new_ref`], this runs
+    /// [`Graph::validate`]: `xadj` must start with zero and never decrease,
+    /// and every element of `adjncy` must be the id of a vertex.
+    pub fn from_csr_checked(xadj: &'a [Idx], adjncy: &'a [Idx]) -> Result<Graph<'a>, GraphError> {
+        let graph = Graph::try_new_ref(xadj, adjncy)?;
+        graph.validate()?;
+        Ok(graph)
+    }
+
+    /// The number of vertices.
+    pub fn num_vertices(&self) -> usize {
+        self.xadj.len() - 1
+    }
+
+    /// The number of undirected edges.
+    ///
+    /// Each edge is stored in both directions, so this is half the length of
+    /// `adjncy`.
+    pub fn num_edges(&self) -> usize {
+        self.adjncy.len() / 2
+    }
+
+    /// The number of neighbors of vertex `v`.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if `v` is not a vertex.
+    pub fn degree(&self, v: usize) -> usize {
+        self.neighbors(v).len()
+    }
+
+    /// The largest number of neighbors of a vertex, or zero if there is no
+    /// vertex.
+    pub fn max_degree(&self) -> usize {
+        self.xadj
+            .windows(2)
+            .map(|w| (w[1] - w[0]) as usize)
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Returns the number of vertices of each degree: the element `d` is the
+    /// number of vertices with `d` neighbors, up to [`Graph::max_degree`].
+    ///
+    /// A skewed distribution, with a few vertices of very high degree, hints
+    /// at a social network, for which the social [`Mode`]s are tuned.
+    pub fn degree_histogram(&self) -> Vec<usize> {
+        let mut histogram = Vec::new();
+        for w in self.xadj.windows(2) {
+            let degree = (w[1] - w[0]) as usize;
+            if histogram.len() <= degree {
+                histogram.resize(degree + 1, 0);
+            }
+            histogram[degree] += 1;
+        }
+        histogram
+    }
+
+    /// The neighbors of vertex `v`.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if `v` is not a vertex.
+    pub fn neighbors(&self, v: usize) -> &[Idx] {
+        assert!(
+            v < self.num_vertices(),
+            "vertex {v} is out of range, the graph has {} vertices",
+            self.num_vertices()
+        );
+        &self.adjncy[self.xadj[v] as usize..self.xadj[v + 1] as usize]
+    }
+
+    /// Iterates over the neighbors of vertex `v`, together with the weight of
+    /// the edge to each of them.
+    ///
+    /// Edges have a weight of 1 if the graph has no edge weights.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if `v` is not a vertex.
+    pub fn edges(&self, v: usize) -> impl Iterator<Item = (Idx, Idx)> + '_ {
+        let neighbors = self.neighbors(v);
+        let start = self.xadj[v] as usize;
+        let weights = self
+            .adjwgt
+            .as_deref()
+            .map(|adjwgt| &adjwgt[start..start + neighbors.len()]);
+        neighbors
+            .iter()
+            .enumerate()
+            .map(move |(i, &u)| (u, weights.map_or(1, |weights| weights[i])))
+    }
+
+    /// Checks the adjacency structure for common mistakes.
+    ///
+    /// This verifies that `xadj` starts with zero and never decreases, and
+    /// that every element of `adjncy` is the id of a vertex. KaHIP is likely
+    /// to crash on graphs that fail these checks.
+    pub fn validate(&self) -> Result<(), ValidationError> {
+        validate_csr(self.xadj, self.adjncy)
+    }
+
+    /// Returns whether the adjacency structure is symmetric.
+    ///
+    /// KaHIP expects every edge `u -> v` to be matched by an edge `v -> u`,
+    /// with the same weight if edge weights are set.
+    ///
+    /// This sorts a copy of the edge list, so it runs in `O(E log E)` time and
+    /// uses `O(E)` memory.
+    pub fn is_symmetric(&self) -> bool {
+        let mut forward = Vec::with_capacity(self.adjncy.len());
+        for (u, w) in self.xadj.windows(2).enumerate() {
+            for k in w[0] as usize..w[1] as usize {
+                let weight = self.adjwgt.as_ref().map_or(1, |adjwgt| adjwgt[k]);
+                forward.push((u as Idx, self.adjncy[k], weight));
+            }
+        }
+        let mut backward: Vec<_> = forward.iter().map(|&(u, v, w)| (v, u, w)).collect();
+
+        forward.sort_unstable();
+        backward.sort_unstable();
+        forward == backward
+    }
+
+    /// Returns whether some vertex is its own neighbor.
+    ///
+    /// KaHIP's behavior on such self-loops is undefined in several modes.
+    pub fn has_self_loops(&self) -> bool {
+        self.xadj
+            .windows(2)
+            .enumerate()
+            .any(|(v, w)| self.adjncy[w[0] as usize..w[1] as usize].contains(&(v as Idx)))
+    }
+
+    /// Returns an owned copy of this graph, with its adjacency structure and
+    /// weights.
+    ///
+    /// The copy does not borrow anything, so it can be kept around to be
+    /// partitioned again later, e.g. with other parameters. Fixed vertices are
+    /// not part of a [`GraphBuf`], and are not copied.
+    pub fn clone_to_buf(&self) -> GraphBuf {
+        GraphBuf {
+            xadj: self.xadj.to_vec(),
+            adjncy: self.adjncy.to_vec(),
+            vwgt: self.vwgt.as_ref().map(|vwgt| vwgt.to_vec()),
+            ncon: self.ncon,
+            adjwgt: self.adjwgt.as_ref().map(|adjwgt| adjwgt.to_vec()),
+        }
+    }
+
+    /// Returns a copy of this graph without its self-loops.
+    ///
+    /// Removing edges shrinks `adjncy`, which cannot be done in place on
+    /// borrowed slices, hence the owned [`GraphBuf`].
+    pub fn without_self_loops(&self) -> GraphBuf {
+        let mut xadj = Vec::with_capacity(self.xadj.len());
+        let mut adjncy = Vec::with_capacity(self.adjncy.len());
+        let mut adjwgt = self
+            .adjwgt
+            .as_ref()
+            .map(|_| Vec::with_capacity(self.adjncy.len()));
+
+        xadj.push(0);
+        for (v, w) in self.xadj.windows(2).enumerate() {
+            for k in w[0] as usize..w[1] as usize {
+                if self.adjncy[k] == v as Idx {
+                    continue;
+                }
+                adjncy.push(self.adjncy[k]);
+                if let (Some(adjwgt), Some(old)) = (adjwgt.as_mut(), self.adjwgt.as_ref()) {
+                    adjwgt.push(old[k]);
+                }
+            }
+            xadj.push(adjncy.len() as Idx);
+        }
+
+        GraphBuf {
+            xadj,
+            adjncy,
+            vwgt: self.vwgt.as_ref().map(|vwgt| vwgt.to_vec()),
+            ncon: self.ncon,
+            adjwgt,
         }
     }
 
     /// Sets the computational weights of the vertices.
     ///
     /// By default all vertices have the same weight.
-    pub fn set_vwgt(mut self, vwgt: &'a mut [Idx]) -> Graph<'a> {
-        assert_eq!(vwgt.len(), self.xadj.len() - 1);
+    ///
+    /// # Panics
+    ///
+    /// This function panics if `vwgt` has not one weight per vertex, see
+    /// [`Graph::try_set_vwgt`].
+    pub fn set_vwgt(self, vwgt: &'a mut [Idx]) -> Graph<'a> {
+        self.try_set_vwgt(vwgt).unwrap()
+    }
+
+    /// Same as [`Graph::set_vwgt`], returning an error if `vwgt` has not one
+    /// weight per vertex.
+    pub fn try_set_vwgt(mut self, vwgt: &'a mut [Idx]) -> Result<Graph<'a>, GraphError> {
+        let expected = self.xadj.len() - 1;
+        if vwgt.len() != expected {
+            return Err(GraphError::VwgtLengthMismatch {
+                expected,
+                got: vwgt.len(),
+            });
+        }
+        self.vwgt = Some(vwgt);
+        self.ncon = 1;
+        Ok(self)
+    }
+
+    /// Sets `ncon` computational weights per vertex, for multi-constraint
+    /// partitioning.
+    ///
+    /// The weights are stored vertex by vertex: the `c`-th weight of vertex
+    /// `v` is `vwgt[v * ncon + c]`.
+    ///
+    /// KaHIP's interface only handles a single weight per vertex, so with
+    /// `ncon > 1` [`Graph::try_partition_with`] returns an error and the other
+    /// computations panic. This is the same as [`Graph::set_vwgt`] when `ncon`
+    /// is 1.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if:
+    /// - `ncon` is zero, or
+    /// - the length of `vwgt` is different than `ncon` times the number of
+    ///   vertices.
+    pub fn set_vwgt_multi(mut self, vwgt: &'a mut [Idx], ncon: usize) -> Graph<'a> {
+        assert_ne!(ncon, 0);
+        assert_eq!(vwgt.len(), ncon * (self.xadj.len() - 1));
         self.vwgt = Some(vwgt);
+        self.ncon = ncon;
         self
     }
 
     /// Sets the weights of the edges.
     ///
     /// By default all edges have the same weight.
-    pub fn set_adjwgt(mut self, adjwgt: &'a mut [Idx]) -> Graph<'a> {
-        assert_eq!(
-            adjwgt.len(),
-            (*self.xadj.last().unwrap()).try_into().unwrap()
-        );
+    ///
+    /// # Panics
+    ///
+    /// This function panics if the lengths of `adjwgt` and `adjncy` are
+    /// different, see [`Graph::try_set_adjwgt`].
+    pub fn set_adjwgt(self, adjwgt: &'a mut [Idx]) -> Graph<'a> {
+        self.try_set_adjwgt(adjwgt).unwrap()
+    }
+
+    /// Same as [`Graph::set_adjwgt`], returning an error if the lengths of
+    /// `adjwgt` and `adjncy` are different.
+    pub fn try_set_adjwgt(mut self, adjwgt: &'a mut [Idx]) -> Result<Graph<'a>, GraphError> {
+        let expected = self.adjncy.len();
+        if adjwgt.len() != expected {
+            return Err(GraphError::AdjwgtLengthMismatch {
+                expected,
+                got: adjwgt.len(),
+            });
+        }
         self.adjwgt = Some(adjwgt);
+        Ok(self)
+    }
+
+    /// Fixes vertices to blocks: vertex `v` ends up in block `fixed[v]`, or
+    /// anywhere if `fixed[v]` is -1.
+    ///
+    /// KaHIP's library interface cannot fix vertices, so this is emulated by
+    /// [`Graph::partition_with`] and the other `kaffpa`-based methods: blocks
+    /// are renamed so that fixed vertices mostly land in their block, and the
+    /// remaining fixed vertices are then moved. Moved vertices may exceed the
+    /// allowed imbalance and increase the edge cut. Since blocks are renamed,
+    /// fixed vertices cannot be combined with different target weights, see
+    /// [`PartitionConfig::set_target_weights`]. Other computations ignore
+    /// fixed vertices.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if the length of `fixed` is different than the
+    /// number of vertices.
+    pub fn set_fixed_vertices(mut self, fixed: &'a [Idx]) -> Graph<'a> {
+        assert_eq!(fixed.len(), self.xadj.len() - 1);
+        self.fixed = Some(fixed);
         self
     }
 
+    /// Returns the number of vertices, or an error if it does not fit in an
+    /// [`Idx`].
+    fn nvtxs(&self) -> Result<Idx, PartitionError> {
+        checked_idx(self.xadj.len() - 1)
+    }
+
+    /// Checks the number of blocks and the imbalance of `cfg` as
+    /// [`PartitionConfig::validate`] does, and that the vertices have a single
+    /// weight, returning the number of vertices.
+    fn check_args(&self, cfg: &PartitionConfig) -> Result<Idx, PartitionError> {
+        cfg.validate(self.xadj.len() - 1)?;
+        if self.ncon != 1 {
+            return Err(PartitionError::MultiConstraintUnsupported { ncon: self.ncon });
+        }
+        self.nvtxs()
+    }
+
+    /// Returns a pointer to the vertex weights, or null if they are unset.
+    fn vwgt_ptr(&mut self) -> *mut Idx {
+        assert_eq!(
+            self.ncon, 1,
+            "KaHIP does not support multi-constraint partitioning"
+        );
+        if let Some(vwgt) = self.vwgt.as_mut() {
+            vwgt.as_mut_ptr()
+        } else {
+            ptr::null_mut()
+        }
+    }
+
+    /// Returns a pointer to the edge weights, or null if they are unset.
+    fn adjwgt_ptr(&mut self) -> *mut Idx {
+        if let Some(adjwgt) = self.adjwgt.as_mut() {
+            adjwgt.as_mut_ptr()
+        } else {
+            ptr::null_mut()
+        }
+    }
+
     /// Partition the graph
+    ///
+    /// Returns the block of each vertex and the edge cut, together with the
+    /// values KaHIP left in its `n_parts` and `imbalance` arguments.
+    ///
+    /// This is a shorthand for [`Graph::partition_with`].
+    ///
+    /// # Panics
+    ///
+    /// This function panics if the arguments are out of range, see
+    /// [`Graph::try_partition_with`].
     pub fn partition(
         &mut self,
         n_parts: Idx,
@@ -97,60 +772,1948 @@ impl<'a> Graph<'a> {
         suppress_output: bool,
         seed: Idx,
         mode: Mode,
-    ) -> (Vec<Idx>, Idx) {
-        let nvtxs = &mut (self.xadj.len() as Idx - 1) as *mut Idx;
-        let xadj = self.xadj.as_mut_ptr();
-        let adjncy = self.adjncy.as_mut_ptr();
-        let vwgt = if let Some(vwgt) = self.vwgt.as_mut() {
-            vwgt.as_mut_ptr()
-        } else {
-            ptr::null_mut()
-        };
-        let adjwgt = if let Some(adjwgt) = self.adjwgt.as_mut() {
-            adjwgt.as_mut_ptr()
-        } else {
-            ptr::null_mut()
-        };
+    ) -> PartitionResult {
+        self.try_partition(n_parts, imbalance, suppress_output, seed, mode)
+            .unwrap()
+    }
 
-        let mut edgecut = mem::MaybeUninit::uninit();
-        let mut part = vec![0; self.xadj.len() - 1];
+    /// Same as [`Graph::partition`], returning an error if the arguments are
+    /// out of range.
+    pub fn try_partition(
+        &mut self,
+        n_parts: Idx,
+        imbalance: f64,
+        suppress_output: bool,
+        seed: Idx,
+        mode: Mode,
+    ) -> Result<PartitionResult, PartitionError> {
+        let cfg = PartitionConfig::new(n_parts)
+            .set_imbalance(imbalance)
+            .set_suppress_output(suppress_output)
+            .set_seed(seed)
+            .set_mode(mode);
+        self.try_partition_with(&cfg)
+    }
 
-        let mut n_parts = n_parts;
-        let mut imbalance = imbalance;
+    /// Partition the graph according to `cfg`.
+    ///
+    /// Returns the block of each vertex and the edge cut, together with the
+    /// values KaHIP left in its `n_parts` and `imbalance` arguments.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if the arguments are out of range, see
+    /// [`Graph::try_partition_with`].
+    pub fn partition_with(&mut self, cfg: &PartitionConfig) -> PartitionResult {
+        self.try_partition_with(cfg).unwrap()
+    }
 
-        unsafe {
-            m::kaffpa(
-                nvtxs,
-                vwgt,
-                xadj,
-                adjwgt,
-                adjncy,
-                &mut n_parts as *mut Idx,
-                &mut imbalance as *mut f64,
-                suppress_output,
-                seed,
-                mode as Idx,
-                edgecut.as_mut_ptr(),
-                part.as_mut_ptr(),
-            );
-            (part, edgecut.assume_init())
-        }
+    /// Partition the graph into `n_parts` blocks with the default options of
+    /// [`PartitionConfig::new`]: an imbalance of 3%, no output from KaHIP, a
+    /// seed of 0 and [`Mode::Eco`].
+    ///
+    /// # Panics
+    ///
+    /// This function panics if `n_parts` is not between 1 and the number of
+    /// vertices, see [`Graph::try_partition_with`].
+    pub fn partition_default(&mut self, n_parts: Idx) -> PartitionResult {
+        self.partition_with(&PartitionConfig::new(n_parts))
     }
-}
 
-#[cfg(test)]
-mod tests {
+    /// Partition the graph reproducibly, with a seed of 0 and KaHIP's output
+    /// suppressed, or returns an error if the arguments are out of range.
+    ///
+    /// KaHIP's partitioner is sequential and only draws random numbers from a
+    /// generator seeded with the given seed, so every [`Mode`] returns the
+    /// same partition for the same graph and arguments. Results may still
+    /// differ between versions or builds of KaHIP.
+    pub fn partition_deterministic(
+        &mut self,
+        n_parts: Idx,
+        imbalance: f64,
+        mode: Mode,
+    ) -> Result<PartitionResult, PartitionError> {
+        let cfg = PartitionConfig::new(n_parts)
+            .set_imbalance(imbalance)
+            .set_mode(mode);
+        self.try_partition_with(&cfg)
+    }
 
-    use crate::{Graph, Mode};
-    #[test]
-    fn test() {
-        let mut xadj = vec![0, 2, 5, 7, 9, 12];
-        let mut adjncy = vec![1, 4, 0, 2, 4, 1, 3, 2, 4, 0, 1, 3];
+    /// Partition the graph once per seed, returning the result with the
+    /// lowest edge cut.
+    ///
+    /// KaHIP's output is suppressed. The edge cut found by KaHIP depends on
+    /// the seed, so trying several of them is a cheap way to improve it, but
+    /// the runtime is multiplied by the number of seeds. The first seed wins
+    /// among results with the same edge cut.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if:
+    /// - `seeds` is empty, or
+    /// - the arguments are out of range, see [`Graph::try_partition_with`].
+    pub fn partition_best_of(
+        &mut self,
+        seeds: &[Idx],
+        n_parts: Idx,
+        imbalance: f64,
+        mode: Mode,
+    ) -> PartitionResult {
+        assert!(!seeds.is_empty(), "at least one seed is required");
+        let cfg = PartitionConfig::new(n_parts)
+            .set_imbalance(imbalance)
+            .set_mode(mode);
+        seeds
+            .iter()
+            .map(|&seed| self.partition_with(&cfg.clone().set_seed(seed)))
+            .reduce(|best, result| {
+                if result.edge_cut < best.edge_cut {
+                    result
+                } else {
+                    best
+                }
+            })
+            .unwrap()
+    }
+
+    /// Partition the graph by recursive bisection, or returns an error if the
+    /// arguments are out of range.
+    ///
+    /// The graph is split in two with KaHIP, then each half is split in two
+    /// again, and so on until there are `n_parts` blocks, which must be a
+    /// power of two. KaHIP's k-way partitioner instead splits the graph into
+    /// all the blocks at once, then refines the blocks together, which
+    /// usually yields a lower edge cut. Bisection may still do better on some
+    /// graphs, at the cost of a higher runtime since KaHIP is called
+    /// `n_parts - 1` times.
+    ///
+    /// The imbalance of each bisection is chosen so that the blocks do not
+    /// exceed the allowed `imbalance` once compounded. A part with no more
+    /// vertices than the blocks it must be split into gives one vertex to
+    /// each block, so `n_parts` may be as large as the number of vertices.
+    /// Vertices fixed with [`Graph::set_fixed_vertices`] are ignored.
+    pub fn partition_recursive(
+        &mut self,
+        n_parts: Idx,
+        imbalance: f64,
+        mode: Mode,
+    ) -> Result<PartitionResult, PartitionError> {
+        let n_vertices = self.nvtxs()?;
+        if n_parts <= 0 || n_parts & (n_parts - 1) != 0 {
+            return Err(PartitionError::NotPowerOfTwo { n_parts });
+        }
+        if imbalance.is_nan() || imbalance < 0.0 {
+            return Err(PartitionError::InvalidImbalance { imbalance });
+        }
+        if n_parts > n_vertices {
+            return Err(PartitionError::InvalidNParts {
+                n_parts,
+                n_vertices,
+            });
+        }
+
+        let levels = n_parts.trailing_zeros() as i32;
+        let cfg = PartitionConfig::new(2)
+            .set_imbalance((1.0 + imbalance).powf(1.0 / levels.max(1) as f64) - 1.0)
+            .set_mode(mode);
+        let ids: Vec<usize> = (0..n_vertices as usize).collect();
+        let mut part = vec![0; n_vertices as usize];
 
-        let (part, edgcut) =
-            Graph::new(&mut xadj, &mut adjncy).partition(2, 0.03, true, 1234, Mode::Eco);
+        let fixed = self.fixed.take();
+        let bisected = bisect(self, &ids, 0, n_parts, &cfg, &mut part);
+        self.fixed = fixed;
+        bisected?;
 
-        assert_eq!(part, [0, 0, 1, 1, 0]);
-        assert_eq!(edgcut, 2);
+        let cut = edge_cut(&*self, &part);
+        Ok(PartitionResult {
+            part,
+            edge_cut: cut,
+            n_parts,
+            imbalance,
+        })
+    }
+
+    /// Partition the graph according to `cfg`, returning a [`Partition`].
+    ///
+    /// This is the same as [`Graph::partition_with`], without the edge cut
+    /// and KaHIP's output arguments.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if the arguments are out of range, see
+    /// [`Graph::try_partition_with`].
+    pub fn partition_typed(&mut self, cfg: &PartitionConfig) -> Partition {
+        let result = self.partition_with(cfg);
+        Partition::new(result.part, cfg.n_parts)
+    }
+
+    /// Partition the graph according to `cfg`, capturing what KaHIP prints.
+    ///
+    /// Some builds of KaHIP print to the standard output even when
+    /// `suppress_output` is set. This redirects the standard output and error
+    /// of the process to a pipe during the call, and returns what was printed
+    /// together with the result, e.g. to forward it to a logger.
+    ///
+    /// The file descriptors are shared by the whole process, so what other
+    /// threads print during the call is captured as well. This is only
+    /// available on Unix.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if the arguments are out of range, see
+    /// [`Graph::try_partition_with`].
+    #[cfg(unix)]
+    pub fn partition_captured(
+        &mut self,
+        cfg: &PartitionConfig,
+    ) -> std::io::Result<(PartitionResult, String)> {
+        let (result, output) = capture::capture(|| self.try_partition_with(cfg))?;
+        Ok((result.unwrap(), output))
+    }
+
+    /// Partition the graph according to `cfg`, also returning the time spent
+    /// in KaHIP.
+    ///
+    /// Only the call to KaHIP is timed, not the allocation of the result. This
+    /// is wall-clock time, measured with [`Instant`], not CPU time.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if the arguments are out of range, see
+    /// [`Graph::try_partition_with`].
+    pub fn partition_timed(&mut self, cfg: &PartitionConfig) -> (PartitionResult, Duration) {
+        let mut part = vec![0; self.xadj.len() - 1];
+        let (edge_cut, n_parts, imbalance, elapsed) = self.kaffpa(&mut part, cfg).unwrap();
+        let result = PartitionResult {
+            part,
+            edge_cut,
+            n_parts,
+            imbalance,
+        };
+        (result, elapsed)
+    }
+
+    /// Partition the graph according to `cfg`, or returns an error if the
+    /// arguments are out of range.
+    ///
+    /// The imbalance must be non-negative and finite, the number of blocks
+    /// must be between 1 and the number of vertices, and the target weights,
+    /// if set, must be one positive number per block, see
+    /// [`PartitionConfig::validate`]. Fixed vertices must be fixed to
+    /// one of the blocks, and the number of vertices must fit in an [`Idx`].
+    /// Multi-constraint partitioning, see [`Graph::set_vwgt_multi`], is not
+    /// supported. An empty graph can be split into any positive number of
+    /// blocks.
+    ///
+    /// KaHIP is not called when there is a single block, nor when the graph
+    /// has no edges: the vertices are then spread over the blocks according
+    /// to their weights, and the edge cut is zero.
+    pub fn try_partition_with(
+        &mut self,
+        cfg: &PartitionConfig,
+    ) -> Result<PartitionResult, PartitionError> {
+        let mut part = vec![0; self.xadj.len() - 1];
+        let (edge_cut, n_parts, imbalance, _) = self.kaffpa(&mut part, cfg)?;
+        Ok(PartitionResult {
+            part,
+            edge_cut,
+            n_parts,
+            imbalance,
+        })
+    }
+
+    /// Partition the graph into a caller-provided buffer.
+    ///
+    /// This is the same as [`Graph::partition`], writing the block of each
+    /// vertex into `part` instead of allocating a new vector. Returns the edge
+    /// cut.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if:
+    /// - the length of `part` is different than the number of vertices, or
+    /// - the arguments are out of range, see [`Graph::try_partition_with`].
+    pub fn partition_into(
+        &mut self,
+        part: &mut [Idx],
+        n_parts: Idx,
+        imbalance: f64,
+        suppress_output: bool,
+        seed: Idx,
+        mode: Mode,
+    ) -> Idx {
+        let cfg = PartitionConfig::new(n_parts)
+            .set_imbalance(imbalance)
+            .set_suppress_output(suppress_output)
+            .set_seed(seed)
+            .set_mode(mode);
+        self.try_partition_into(part, &cfg).unwrap()
+    }
+
+    /// Partition the graph according to `cfg` into a caller-provided buffer,
+    /// or returns an error if `part` has not one element per vertex or if the
+    /// arguments are out of range, see [`Graph::try_partition_with`].
+    ///
+    /// Returns the edge cut. `part` is left untouched on error.
+    pub fn try_partition_into(
+        &mut self,
+        part: &mut [Idx],
+        cfg: &PartitionConfig,
+    ) -> Result<Idx, PartitionError> {
+        let (edge_cut, _, _, _) = self.kaffpa(part, cfg)?;
+        Ok(edge_cut)
+    }
+
+    /// Calls `kaffpa`, returning the edge cut, the values it left in
+    /// `n_parts` and `imbalance`, and the time spent in KaHIP.
+    ///
+    /// `part` must have one element per vertex.
+    fn kaffpa(
+        &mut self,
+        part: &mut [Idx],
+        cfg: &PartitionConfig,
+    ) -> Result<(Idx, Idx, f64, Duration), PartitionError> {
+        if part.len() != self.xadj.len() - 1 {
+            return Err(PartitionError::PartLengthMismatch {
+                expected: self.xadj.len() - 1,
+                got: part.len(),
+            });
+        }
+        cfg.validate(self.xadj.len() - 1)?;
+        if self.ncon != 1 {
+            return Err(PartitionError::MultiConstraintUnsupported { ncon: self.ncon });
+        }
+        if let Some(fixed) = self.fixed {
+            if let Some((vertex, &block)) = fixed
+                .iter()
+                .enumerate()
+                .find(|(_, &f)| f != -1 && !(0..cfg.n_parts).contains(&f))
+            {
+                return Err(PartitionError::FixedBlockOutOfRange { vertex, block });
+            }
+            // Blocks are renamed after the partition, which would give the
+            // weight meant for a block to another one.
+            if let Some(targets) = &cfg.target_weights {
+                if targets.iter().any(|&t| t != targets[0]) {
+                    return Err(PartitionError::FixedWithTargetWeights);
+                }
+            }
+        }
+        if cfg.n_parts == 1 {
+            part.fill(0);
+            return Ok((0, 1, cfg.imbalance, Duration::ZERO));
+        }
+        if self.adjncy.is_empty() {
+            partition::spread_isolated(
+                part,
+                self.vwgt.as_deref(),
+                self.fixed,
+                cfg.target_weights.as_deref(),
+                cfg.n_parts,
+            );
+            return Ok((0, cfg.n_parts, cfg.imbalance, Duration::ZERO));
+        }
+
+        let (mut cut, n_parts, imbalance, elapsed) = self.kaffpa_targets(part, cfg);
+        if let Some(fixed) = self.fixed {
+            partition::pin_fixed_vertices(part, fixed, cfg.n_parts);
+            cut = edge_cut(&*self, part);
+        }
+        Ok((cut, n_parts, imbalance, elapsed))
+    }
+
+    /// Calls `kaffpa` with valid arguments, splitting the graph according to
+    /// the target weights if they are set.
+    fn kaffpa_targets(
+        &mut self,
+        part: &mut [Idx],
+        cfg: &PartitionConfig,
+    ) -> (Idx, Idx, f64, Duration) {
+        let Some(targets) = &cfg.target_weights else {
+            return self.kaffpa_unchecked(part, cfg, cfg.n_parts);
+        };
+
+        // Split into equal blocks, then merge them according to the targets.
+        let max_sub_blocks = cfg
+            .n_parts
+            .saturating_mul(32)
+            .min(self.xadj.len() as Idx - 1);
+        let counts = config::sub_blocks(targets, max_sub_blocks, cfg.imbalance);
+        let n_sub_blocks = counts.iter().sum();
+        let (_, _, _, elapsed) = self.kaffpa_unchecked(part, cfg, n_sub_blocks);
+        let owner: Vec<Idx> = counts
+            .iter()
+            .enumerate()
+            .flat_map(|(block, &count)| iter::repeat_n(block as Idx, count as usize))
+            .collect();
+        for p in part.iter_mut() {
+            *p = owner[*p as usize];
+        }
+
+        // KaHIP's imbalance is the one of the sub-blocks, measure the merged
+        // blocks against their targets instead.
+        let weights = block_weights(part, self.vwgt.as_deref(), cfg.n_parts);
+        let total = weights.iter().sum::<i64>() as f64;
+        let imbalance = if total == 0.0 {
+            0.0
+        } else {
+            weights
+                .iter()
+                .zip(targets)
+                .map(|(&weight, target)| weight as f64 / (target * total) - 1.0)
+                .fold(f64::NEG_INFINITY, f64::max)
+        };
+        (edge_cut(&*self, part), cfg.n_parts, imbalance, elapsed)
+    }
+
+    /// Calls `kaffpa` with `n_parts` blocks and the other options of `cfg`.
+    fn kaffpa_unchecked(
+        &mut self,
+        part: &mut [Idx],
+        cfg: &PartitionConfig,
+        n_parts: Idx,
+    ) -> (Idx, Idx, f64, Duration) {
+        // KaHIP only reads the vertex count, but it must still outlive the
+        // call since it is passed by pointer.
+        let mut nvtxs = self.xadj.len() as Idx - 1;
+        // Only read by KaHIP, see `Graph::new_ref`.
+        let xadj = self.xadj.as_ptr() as *mut Idx;
+        let adjncy = self.adjncy.as_ptr() as *mut Idx;
+        let vwgt = self.vwgt_ptr();
+        let adjwgt = self.adjwgt_ptr();
+
+        let mut edgecut = mem::MaybeUninit::uninit();
+
+        let mut n_parts = n_parts;
+        let mut imbalance = cfg.imbalance;
+
+        #[cfg(feature = "logging")]
+        log::trace!(
+            "calling kaffpa with {} blocks, mode {:?}, imbalance {}",
+            n_parts,
+            cfg.mode,
+            imbalance
+        );
+        let _guard = lock_kahip();
+        let start = Instant::now();
+        let edgecut = unsafe {
+            m::kaffpa(
+                &mut nvtxs,
+                vwgt,
+                xadj,
+                adjwgt,
+                adjncy,
+                &mut n_parts as *mut Idx,
+                &mut imbalance as *mut f64,
+                cfg.suppress_output,
+                cfg.seed,
+                cfg.mode as Idx,
+                edgecut.as_mut_ptr(),
+                part.as_mut_ptr(),
+            );
+            edgecut.assume_init()
+        };
+        let elapsed = start.elapsed();
+
+        #[cfg(feature = "logging")]
+        log::debug!(
+            "kaffpa: {} vertices, {} edges, {} blocks, mode {:?}, imbalance {}: edge cut {} in {:?}",
+            nvtxs,
+            self.adjncy.len() / 2,
+            n_parts,
+            cfg.mode,
+            cfg.imbalance,
+            edgecut,
+            elapsed
+        );
+
+        (edgecut, n_parts, imbalance, elapsed)
+    }
+
+    /// Partition the graph, returning the block of each vertex and the edge
+    /// cut.
+    #[deprecated(note = "use `partition`, which returns a `PartitionResult`")]
+    pub fn partition_tuple(
+        &mut self,
+        n_parts: Idx,
+        imbalance: f64,
+        suppress_output: bool,
+        seed: Idx,
+        mode: Mode,
+    ) -> (Vec<Idx>, Idx) {
+        let result = self.partition(n_parts, imbalance, suppress_output, seed, mode);
+        (result.part, result.edge_cut)
+    }
+
+    /// Partitions the graph into `n_parts` blocks with KaHIP's
+    /// `kaffpa_balance_NE`, which balances the blocks on both their vertices
+    /// and their edges.
+    ///
+    /// With [`Graph::partition`], the weight of a block is the total weight
+    /// of its vertices, even with an imbalance of zero: a block of few
+    /// high-degree vertices may hold many more edges than the others. Here,
+    /// each vertex also weighs the total weight of its edges, which is closer
+    /// to the cost of, e.g., a sparse matrix-vector product. The blocks are
+    /// balanced on this combined weight with no allowed imbalance. When all
+    /// vertices have the same weight and degree, the block sizes differ by at
+    /// most one.
+    ///
+    /// This is only available when the installed KaHIP declares
+    /// `kaffpa_balance_NE`, which older releases do not.
+    ///
+    /// Returns an error if `n_parts` is not between 1 and the number of
+    /// vertices, or if the graph has several vertex weights per vertex.
+    #[cfg(kahip_balance_ne)]
+    pub fn partition_balanced(
+        &mut self,
+        n_parts: Idx,
+        suppress_output: bool,
+        seed: Idx,
+        mode: Mode,
+    ) -> Result<PartitionResult, PartitionError> {
+        let cfg = PartitionConfig::new(n_parts).set_imbalance(0.0);
+        let mut nvtxs = self.check_args(&cfg)?;
+        if nvtxs == 0 || n_parts == 1 {
+            return Ok(PartitionResult {
+                part: vec![0; nvtxs as usize],
+                edge_cut: 0,
+                n_parts,
+                imbalance: 0.0,
+            });
+        }
+        let xadj = self.xadj.as_ptr() as *mut Idx;
+        let adjncy = self.adjncy.as_ptr() as *mut Idx;
+        let vwgt = self.vwgt_ptr();
+        let adjwgt = self.adjwgt_ptr();
+
+        let mut edgecut = mem::MaybeUninit::uninit();
+        let mut part = vec![0; self.xadj.len() - 1];
+
+        let mut n_parts = n_parts;
+        let mut imbalance = 0.0;
+
+        let _guard = lock_kahip();
+        let edge_cut = unsafe {
+            m::kaffpa_balance_NE(
+                &mut nvtxs,
+                vwgt,
+                xadj,
+                adjwgt,
+                adjncy,
+                &mut n_parts,
+                &mut imbalance,
+                suppress_output,
+                seed,
+                mode as Idx,
+                edgecut.as_mut_ptr(),
+                part.as_mut_ptr(),
+            );
+            edgecut.assume_init()
+        };
+        Ok(PartitionResult {
+            part,
+            edge_cut,
+            n_parts,
+            imbalance,
+        })
+    }
+
+    /// Computes a node separator of the graph, or returns an error if the
+    /// arguments are out of range.
+    ///
+    /// Returns the label of each vertex and the size of the separator. As in
+    /// the output of KaHIP's command-line `node_separator` program, vertices
+    /// of the separator are labeled `n_parts`, and the remaining vertices are
+    /// labeled with a block in `0..n_parts`, such that no edge joins two
+    /// different blocks.
+    ///
+    /// KaHIP's library interface only reports the separator vertices. The
+    /// blocks of the other vertices are rebuilt from the connected components
+    /// left once the separator is removed, each component going to the block
+    /// of least vertex weight, from the heaviest component to the lightest.
+    /// They may thus differ from the blocks KaHIP computed internally.
+    pub fn node_separator(
+        &mut self,
+        n_parts: Idx,
+        imbalance: f64,
+        suppress_output: bool,
+        seed: Idx,
+        mode: Mode,
+    ) -> Result<(Vec<Idx>, Idx), PartitionError> {
+        let cfg = PartitionConfig::new(n_parts).set_imbalance(imbalance);
+        let mut nvtxs = self.check_args(&cfg)?;
+        if nvtxs == 0 || n_parts == 1 {
+            return Ok((vec![0; nvtxs as usize], 0));
+        }
+        let xadj = self.xadj.as_ptr() as *mut Idx;
+        let adjncy = self.adjncy.as_ptr() as *mut Idx;
+        let vwgt = self.vwgt_ptr();
+        let adjwgt = self.adjwgt_ptr();
+
+        let mut num_separator_vertices = 0;
+        let mut separator = ptr::null_mut();
+
+        let mut n_parts = n_parts;
+        let mut imbalance = imbalance;
+
+        let guard = lock_kahip();
+        let ids = unsafe {
+            m::node_separator(
+                &mut nvtxs,
+                vwgt,
+                xadj,
+                adjwgt,
+                adjncy,
+                &mut n_parts,
+                &mut imbalance,
+                suppress_output,
+                seed,
+                mode as Idx,
+                &mut num_separator_vertices,
+                &mut separator,
+            );
+            if separator.is_null() {
+                Vec::new()
+            } else {
+                // KaHIP hands over an array allocated with C++ `new[]`, which
+                // can only be released by C++ `delete[]`, and it provides no
+                // function to do so. The array is copied and leaked instead:
+                // it holds one integer per separator vertex.
+                std::slice::from_raw_parts(separator, num_separator_vertices as usize).to_vec()
+            }
+        };
+        drop(guard);
+
+        let label = contiguity::separator_labels((&*self).into(), &ids, cfg.n_parts);
+        Ok((label, num_separator_vertices))
+    }
+
+    /// Computes a fill-reducing ordering of the graph by nested dissection.
+    ///
+    /// The returned array maps each vertex to its position in the
+    /// elimination order, i.e. vertex `v` is eliminated at step
+    /// `ordering[v]`. This is what METIS calls the inverse permutation
+    /// (`iperm`).
+    ///
+    /// The separators of each level are computed with the given `mode`,
+    /// which KaHIP's `reduced_nd` takes along with the seed. Vertex and edge
+    /// weights are ignored.
+    ///
+    /// Returns an error if the number of vertices does not fit in an [`Idx`].
+    pub fn nested_dissection(
+        &mut self,
+        suppress_output: bool,
+        seed: Idx,
+        mode: Mode,
+    ) -> Result<Vec<Idx>, PartitionError> {
+        let mut nvtxs = self.nvtxs()?;
+        if nvtxs == 0 {
+            return Ok(Vec::new());
+        }
+        let xadj = self.xadj.as_ptr() as *mut Idx;
+        let adjncy = self.adjncy.as_ptr() as *mut Idx;
+
+        let mut ordering = vec![0; self.xadj.len() - 1];
+
+        let _guard = lock_kahip();
+        unsafe {
+            m::reduced_nd(
+                &mut nvtxs,
+                xadj,
+                adjncy,
+                suppress_output,
+                seed,
+                mode as Idx,
+                ordering.as_mut_ptr(),
+            );
+        }
+        Ok(ordering)
+    }
+
+    /// Partitions the edges of the graph, or returns an error if the
+    /// arguments are out of range, as with [`Graph::try_partition`].
+    ///
+    /// Returns the block of each edge, and the vertex cut, that is the number
+    /// of times vertices are replicated because their edges span several
+    /// blocks.
+    ///
+    /// Unlike [`Graph::partition`], the output is indexed like `adjncy`: the
+    /// edge from `v` to `adjncy[e]`, with `xadj[v] <= e < xadj[v+1]`, is
+    /// assigned to block `part[e]`. Both directions of an undirected edge get
+    /// the same block.
+    pub fn edge_partition(
+        &mut self,
+        n_parts: Idx,
+        imbalance: f64,
+        suppress_output: bool,
+        seed: Idx,
+        mode: Mode,
+    ) -> Result<(Vec<Idx>, Idx), PartitionError> {
+        let cfg = PartitionConfig::new(n_parts).set_imbalance(imbalance);
+        let mut nvtxs = self.check_args(&cfg)?;
+        if self.adjncy.is_empty() || n_parts == 1 {
+            return Ok((vec![0; self.adjncy.len()], 0));
+        }
+        let xadj = self.xadj.as_ptr() as *mut Idx;
+        let adjncy = self.adjncy.as_ptr() as *mut Idx;
+        let vwgt = self.vwgt_ptr();
+        let adjwgt = self.adjwgt_ptr();
+
+        let mut vertexcut = mem::MaybeUninit::uninit();
+        let mut part = vec![0; self.adjncy.len()];
+
+        let mut n_parts = n_parts;
+        let mut imbalance = imbalance;
+
+        let _guard = lock_kahip();
+        unsafe {
+            m::edge_partitioning(
+                &mut nvtxs,
+                vwgt,
+                xadj,
+                adjwgt,
+                adjncy,
+                &mut n_parts,
+                &mut imbalance,
+                suppress_output,
+                seed,
+                mode as Idx,
+                vertexcut.as_mut_ptr(),
+                part.as_mut_ptr(),
+            );
+            Ok((part, vertexcut.assume_init()))
+        }
+    }
+
+    /// Maps the vertices of the graph onto a hierarchical processor topology.
+    ///
+    /// The topology is described level by level, from the innermost to the
+    /// outermost: `hierarchy[i]` is the number of level-`i` elements grouped
+    /// together at level `i+1`, and `distance[i]` is the communication cost
+    /// between two processors whose closest common level is `i`. For example
+    /// `hierarchy = [4, 8, 8]` and `distance = [1, 10, 100]` describe 8 nodes
+    /// of 8 processors with 4 cores each, where cores of the same processor
+    /// communicate at cost 1, processors of the same node at cost 10, and
+    /// nodes at cost 100.
+    ///
+    /// Returns the processor of each vertex, along with the edge cut and the
+    /// communication cost of the mapping, see [`MappingResult`].
+    ///
+    /// Returns an error if:
+    /// - `hierarchy` and `distance` have different lengths,
+    /// - the elements of `hierarchy` are not positive or their product is
+    ///   different than `n_parts`, or
+    /// - the other arguments are out of range, as with
+    ///   [`Graph::try_partition`].
+    #[allow(clippy::too_many_arguments)]
+    pub fn process_mapping(
+        &mut self,
+        hierarchy: &[Idx],
+        distance: &[Idx],
+        n_parts: Idx,
+        imbalance: f64,
+        suppress_output: bool,
+        seed: Idx,
+        mode: Mode,
+        map_mode: MapMode,
+    ) -> Result<MappingResult, PartitionError> {
+        if hierarchy.len() != distance.len() {
+            return Err(PartitionError::HierarchyLengthMismatch {
+                hierarchy: hierarchy.len(),
+                distance: distance.len(),
+            });
+        }
+        let product = hierarchy.iter().try_fold(1 as Idx, |product, &level| {
+            (level > 0).then(|| product.checked_mul(level)).flatten()
+        });
+        if product != Some(n_parts) {
+            return Err(PartitionError::InvalidHierarchy { n_parts });
+        }
+        let cfg = PartitionConfig::new(n_parts).set_imbalance(imbalance);
+        let mut nvtxs = self.check_args(&cfg)?;
+        if nvtxs == 0 {
+            return Ok(MappingResult {
+                part: Vec::new(),
+                edge_cut: 0,
+                qap: 0,
+            });
+        }
+        let xadj = self.xadj.as_ptr() as *mut Idx;
+        let adjncy = self.adjncy.as_ptr() as *mut Idx;
+        let vwgt = self.vwgt_ptr();
+        let adjwgt = self.adjwgt_ptr();
+
+        let mut hierarchy = hierarchy.to_vec();
+        let mut distance = distance.to_vec();
+
+        let mut edgecut = mem::MaybeUninit::uninit();
+        let mut qap = mem::MaybeUninit::uninit();
+        let mut part = vec![0; self.xadj.len() - 1];
+
+        let mut imbalance = imbalance;
+
+        let _guard = lock_kahip();
+        unsafe {
+            m::process_mapping(
+                &mut nvtxs,
+                vwgt,
+                xadj,
+                adjwgt,
+                adjncy,
+                hierarchy.as_mut_ptr(),
+                distance.as_mut_ptr(),
+                hierarchy.len() as Idx,
+                mode as Idx,
+                map_mode as Idx,
+                &mut imbalance,
+                suppress_output,
+                seed,
+                edgecut.as_mut_ptr(),
+                qap.as_mut_ptr(),
+                part.as_mut_ptr(),
+            );
+            Ok(MappingResult {
+                part,
+                edge_cut: edgecut.assume_init(),
+                qap: qap.assume_init(),
+            })
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+/// Splits `graph` into `n_parts` blocks, numbered from `first`, by recursive
+/// bisection. The block of vertex `i` of `graph` is written in `part[ids[i]]`.
+fn bisect(
+    graph: &mut Graph,
+    ids: &[usize],
+    first: Idx,
+    n_parts: Idx,
+    cfg: &PartitionConfig,
+    part: &mut [Idx],
+) -> Result<(), PartitionError> {
+    if n_parts == 1 {
+        for &v in ids {
+            part[v] = first;
+        }
+        return Ok(());
+    }
+    if ids.len() <= n_parts as usize {
+        // Too few vertices to split further, e.g. when `n_parts` is the number
+        // of vertices or a half came out small: one vertex per block.
+        for (block, &v) in (first..).zip(ids) {
+            part[v] = block;
+        }
+        return Ok(());
+    }
+
+    let halves = graph.try_partition_with(cfg)?.part;
+    for half in 0..2 {
+        let (mut subgraph, sub_ids) = induced_subgraph(graph, &halves, half);
+        let sub_ids: Vec<usize> = sub_ids.into_iter().map(|i| ids[i]).collect();
+        let first = first + half * n_parts / 2;
+        bisect(
+            &mut subgraph.as_graph(),
+            &sub_ids,
+            first,
+            n_parts / 2,
+            cfg,
+            part,
+        )?;
+    }
+    Ok(())
+}
+
+#[cfg(feature = "std")]
+/// The checks of [`Graph::validate`], on a non-empty `xadj`.
+pub(crate) fn validate_csr(xadj: &[Idx], adjncy: &[Idx]) -> Result<(), ValidationError> {
+    let nvtxs = xadj.len() as Idx - 1;
+
+    if xadj[0] != 0 {
+        return Err(ValidationError::NonZeroFirstXadj { value: xadj[0] });
+    }
+    for (index, w) in xadj.windows(2).enumerate() {
+        if w[1] < w[0] {
+            return Err(ValidationError::DecreasingXadj {
+                index: index + 1,
+                value: w[1],
+            });
+        }
+    }
+    for (index, &value) in adjncy.iter().enumerate() {
+        if !(0..nvtxs).contains(&value) {
+            return Err(ValidationError::NeighborOutOfRange { index, value });
+        }
+    }
+    Ok(())
+}
+
+#[cfg(feature = "std")]
+/// Converts a length to an [`Idx`], or returns an error if it does not fit.
+fn checked_idx(len: usize) -> Result<Idx, PartitionError> {
+    Idx::try_from(len).map_err(|_| PartitionError::IndexOverflow { len })
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+
+    use crate::testgraphs::grid_2d;
+    use crate::{
+        checked_idx, edge_cut, kahip_version, set_num_threads, Graph, GraphBuf, GraphError, Idx,
+        Imbalance, MapMode, Mode, PartitionConfig, PartitionError, Quality, ValidationError,
+    };
+    use core::time::Duration;
+    #[cfg(feature = "idx64")]
+    const _: () = assert!(core::mem::size_of::<Idx>() == 8);
+
+    #[test]
+    fn test() {
+        let mut xadj = vec![0, 2, 5, 7, 9, 12];
+        let mut adjncy = vec![1, 4, 0, 2, 4, 1, 3, 2, 4, 0, 1, 3];
+
+        let result = Graph::new(&mut xadj, &mut adjncy).partition(2, 0.03, true, 1234, Mode::Eco);
+
+        assert_eq!(result.part, [0, 0, 1, 1, 0]);
+        assert_eq!(result.edge_cut, 2);
+        assert_eq!(result.n_parts, 2);
+    }
+
+    #[test]
+    fn new_ref() {
+        let grid = grid_2d(10, 10);
+        let (xadj, adjncy) = (grid.xadj, grid.adjncy);
+        let mut xadj_mut = xadj.clone();
+        let mut adjncy_mut = adjncy.clone();
+
+        let cfg = PartitionConfig::new(4).set_seed(1234);
+        let result = Graph::new_ref(&xadj, &adjncy).partition_with(&cfg);
+
+        assert_eq!(
+            result,
+            Graph::new(&mut xadj_mut, &mut adjncy_mut).partition_with(&cfg)
+        );
+        assert_eq!((xadj_mut, adjncy_mut), (xadj, adjncy));
+        assert!(Graph::try_new_ref(&[0, 2], &[]).is_err());
+    }
+
+    #[test]
+    fn try_new() {
+        let mut xadj = vec![0, 2, 5, 7, 9, 12];
+        let mut adjncy = vec![1, 4, 0, 2, 4, 1, 3, 2, 4, 0, 1, 3];
+        assert!(Graph::try_new(&mut xadj, &mut adjncy).is_ok());
+
+        let mut adjncy = vec![1, 4, 0, 2];
+        assert_eq!(
+            Graph::try_new(&mut xadj, &mut adjncy),
+            Err(GraphError::AdjncyLengthMismatch {
+                expected: 12,
+                got: 4
+            })
+        );
+
+        assert_eq!(Graph::try_new(&mut [], &mut []), Err(GraphError::EmptyXadj));
+    }
+
+    #[test]
+    fn accessors() {
+        let mut xadj = vec![0, 2, 5, 7, 9, 12];
+        let mut adjncy = vec![1, 4, 0, 2, 4, 1, 3, 2, 4, 0, 1, 3];
+        let graph = Graph::new(&mut xadj, &mut adjncy);
+
+        assert_eq!(graph.num_vertices(), 5);
+        assert_eq!(graph.num_edges(), 6);
+        assert_eq!(graph.degree(1), 3);
+        assert_eq!(graph.neighbors(1), [0, 2, 4]);
+        assert_eq!(graph.neighbors(4), [0, 1, 3]);
+        let degrees: usize = (0..5).map(|v| graph.degree(v)).sum();
+        assert_eq!(degrees, 2 * graph.num_edges());
+    }
+
+    #[test]
+    fn degree_distribution() {
+        let mut xadj = vec![0, 2, 5, 7, 9, 12];
+        let mut adjncy = vec![1, 4, 0, 2, 4, 1, 3, 2, 4, 0, 1, 3];
+        let graph = Graph::new(&mut xadj, &mut adjncy);
+        assert_eq!(graph.max_degree(), 3);
+        assert_eq!(graph.degree_histogram(), [0, 0, 3, 2]);
+
+        let graph = Graph::new_ref(&[0, 0, 0], &[]);
+        assert_eq!(graph.max_degree(), 0);
+        assert_eq!(graph.degree_histogram(), [2]);
+        let graph = Graph::new_ref(&[0], &[]);
+        assert_eq!(graph.max_degree(), 0);
+        assert_eq!(graph.degree_histogram(), []);
+    }
+
+    #[test]
+    fn edges() {
+        let mut xadj = vec![0, 2, 5, 7, 9, 12];
+        let mut adjncy = vec![1, 4, 0, 2, 4, 1, 3, 2, 4, 0, 1, 3];
+        let mut adjwgt = vec![1, 2, 1, 3, 4, 3, 5, 5, 6, 2, 4, 6];
+
+        let graph = Graph::new(&mut xadj, &mut adjncy);
+        assert!(graph.edges(1).eq([(0, 1), (2, 1), (4, 1)]));
+
+        let graph = graph.set_adjwgt(&mut adjwgt);
+        assert!(graph.edges(1).eq([(0, 1), (2, 3), (4, 4)]));
+        assert!(graph.edges(4).eq([(0, 2), (1, 4), (3, 6)]));
+        // The iterator only borrows the graph.
+        assert!(graph
+            .edges(0)
+            .all(|(u, w)| graph.edges(u as usize).any(|e| e == (0, w))));
+    }
+
+    #[test]
+    #[should_panic]
+    fn neighbors_out_of_range() {
+        let mut xadj = vec![0, 2, 5, 7, 9, 12];
+        let mut adjncy = vec![1, 4, 0, 2, 4, 1, 3, 2, 4, 0, 1, 3];
+        Graph::new(&mut xadj, &mut adjncy).neighbors(5);
+    }
+
+    #[test]
+    fn from_csr_checked() {
+        let xadj = [0, 2, 5, 7, 9, 12];
+        let adjncy = [1, 4, 0, 2, 4, 1, 3, 2, 4, 0, 1, 3];
+        assert!(Graph::from_csr_checked(&xadj, &adjncy).is_ok());
+
+        assert_eq!(
+            Graph::from_csr_checked(&[0, 5, 2, 7, 9, 12], &adjncy),
+            Err(GraphError::Invalid(ValidationError::DecreasingXadj {
+                index: 2,
+                value: 2
+            }))
+        );
+        let adjncy = [1, 4, 0, 2, 4, 1, 3, 2, 5, 0, 1, 3];
+        assert_eq!(
+            Graph::from_csr_checked(&xadj, &adjncy),
+            Err(GraphError::Invalid(ValidationError::NeighborOutOfRange {
+                index: 8,
+                value: 5
+            }))
+        );
+        assert_eq!(
+            Graph::from_csr_checked(&xadj, &adjncy[..11]),
+            Err(GraphError::AdjncyLengthMismatch {
+                expected: 12,
+                got: 11
+            })
+        );
+    }
+
+    #[test]
+    fn validate() {
+        let mut xadj = vec![0, 2, 5, 7, 9, 12];
+        let mut adjncy = vec![1, 4, 0, 2, 4, 1, 3, 2, 4, 0, 1, 3];
+        assert_eq!(Graph::new(&mut xadj, &mut adjncy).validate(), Ok(()));
+
+        let mut xadj = vec![1, 2, 5, 7, 9, 12];
+        assert_eq!(
+            Graph::new(&mut xadj, &mut adjncy).validate(),
+            Err(ValidationError::NonZeroFirstXadj { value: 1 })
+        );
+
+        let mut xadj = vec![0, 5, 2, 7, 9, 12];
+        assert_eq!(
+            Graph::new(&mut xadj, &mut adjncy).validate(),
+            Err(ValidationError::DecreasingXadj { index: 2, value: 2 })
+        );
+
+        let mut xadj = vec![0, 2, 5, 7, 9, 12];
+        let mut adjncy = vec![1, 4, 0, 2, 4, 1, 3, 2, 5, 0, 1, 3];
+        assert_eq!(
+            Graph::new(&mut xadj, &mut adjncy).validate(),
+            Err(ValidationError::NeighborOutOfRange { index: 8, value: 5 })
+        );
+
+        let mut adjncy = vec![1, 4, 0, 2, 4, -1, 3, 2, 4, 0, 1, 3];
+        assert_eq!(
+            Graph::new(&mut xadj, &mut adjncy).validate(),
+            Err(ValidationError::NeighborOutOfRange {
+                index: 5,
+                value: -1
+            })
+        );
+    }
+
+    #[test]
+    fn self_loops() {
+        let mut xadj = vec![0, 2, 5, 7, 9, 12];
+        let mut adjncy = vec![1, 4, 0, 2, 4, 1, 3, 2, 4, 0, 1, 3];
+        let graph = Graph::new(&mut xadj, &mut adjncy);
+        assert!(!graph.has_self_loops());
+        assert_eq!(graph.without_self_loops().adjncy(), graph.adjncy);
+
+        // Loops on vertices 1 and 3.
+        let mut xadj = vec![0, 2, 6, 8, 11, 14];
+        let mut adjncy = vec![1, 4, 0, 1, 2, 4, 1, 3, 2, 3, 4, 0, 1, 3];
+        let mut adjwgt = vec![1, 2, 1, 9, 3, 4, 3, 5, 5, 9, 6, 2, 4, 6];
+        let graph = Graph::new(&mut xadj, &mut adjncy).set_adjwgt(&mut adjwgt);
+        assert!(graph.has_self_loops());
+
+        let buf = graph.without_self_loops();
+        assert_eq!(buf.xadj(), [0, 2, 5, 7, 9, 12]);
+        assert_eq!(buf.adjncy(), [1, 4, 0, 2, 4, 1, 3, 2, 4, 0, 1, 3]);
+        assert_eq!(
+            buf.adjwgt(),
+            Some(&[1, 2, 1, 3, 4, 3, 5, 5, 6, 2, 4, 6][..])
+        );
+        assert_eq!(buf.vwgt(), None);
+    }
+
+    #[test]
+    fn is_symmetric() {
+        let mut xadj = vec![0, 2, 5, 7, 9, 12];
+        let mut adjncy = vec![1, 4, 0, 2, 4, 1, 3, 2, 4, 0, 1, 3];
+        let mut adjwgt = vec![1, 2, 1, 3, 4, 3, 5, 5, 6, 2, 4, 6];
+        assert!(Graph::new(&mut xadj, &mut adjncy).is_symmetric());
+        assert!(Graph::new(&mut xadj, &mut adjncy)
+            .set_adjwgt(&mut adjwgt)
+            .is_symmetric());
+
+        // The weights of 3 -> 4 and 4 -> 3 differ.
+        adjwgt[8] = 7;
+        assert!(!Graph::new(&mut xadj, &mut adjncy)
+            .set_adjwgt(&mut adjwgt)
+            .is_symmetric());
+
+        // 0 -> 1 is replaced by 0 -> 2.
+        let mut adjncy = vec![2, 4, 0, 2, 4, 1, 3, 2, 4, 0, 1, 3];
+        assert!(!Graph::new(&mut xadj, &mut adjncy).is_symmetric());
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    fn partition_tuple() {
+        let mut xadj = vec![0, 2, 5, 7, 9, 12];
+        let mut adjncy = vec![1, 4, 0, 2, 4, 1, 3, 2, 4, 0, 1, 3];
+        let mut graph = Graph::new(&mut xadj, &mut adjncy);
+
+        let result = graph.partition(2, 0.03, true, 1234, Mode::Eco);
+        let (part, edgecut) = graph.partition_tuple(2, 0.03, true, 1234, Mode::Eco);
+
+        assert_eq!(part, result.part);
+        assert_eq!(edgecut, result.edge_cut);
+    }
+
+    #[test]
+    fn partition_with() {
+        let mut grid = grid_2d(10, 10);
+        let mut graph = grid.as_graph();
+
+        let cfg = PartitionConfig::new(4).set_seed(1234);
+        assert_eq!(
+            graph.partition_with(&cfg),
+            graph.partition(4, 0.03, true, 1234, Mode::Eco)
+        );
+
+        let cfg = cfg.set_imbalance(0.1).set_mode(Mode::Fast);
+        assert_eq!(
+            graph.partition_with(&cfg),
+            graph.partition(4, 0.1, true, 1234, Mode::Fast)
+        );
+    }
+
+    #[test]
+    fn partition_invalid_arguments() {
+        let mut xadj = vec![0, 2, 5, 7, 9, 12];
+        let mut adjncy = vec![1, 4, 0, 2, 4, 1, 3, 2, 4, 0, 1, 3];
+        let mut graph = Graph::new(&mut xadj, &mut adjncy);
+
+        let cfg = PartitionConfig::new(2).set_imbalance(-0.1);
+        assert_eq!(
+            graph.try_partition_with(&cfg),
+            Err(PartitionError::InvalidImbalance { imbalance: -0.1 })
+        );
+        let cfg = PartitionConfig::new(2).set_imbalance(f64::NAN);
+        assert!(matches!(
+            graph.try_partition_with(&cfg),
+            Err(PartitionError::InvalidImbalance { .. })
+        ));
+
+        for n_parts in [-1, 0, 6] {
+            assert_eq!(
+                graph.try_partition_with(&PartitionConfig::new(n_parts)),
+                Err(PartitionError::InvalidNParts {
+                    n_parts,
+                    n_vertices: 5
+                })
+            );
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn partition_zero_parts() {
+        let mut xadj = vec![0, 2, 5, 7, 9, 12];
+        let mut adjncy = vec![1, 4, 0, 2, 4, 1, 3, 2, 4, 0, 1, 3];
+        Graph::new(&mut xadj, &mut adjncy).partition(0, 0.03, true, 1234, Mode::Eco);
+    }
+
+    #[test]
+    fn partition_recursive() {
+        let mut grid = grid_2d(10, 10);
+        let mut graph = grid.as_graph();
+
+        let result = graph.partition_recursive(4, 0.03, Mode::Eco).unwrap();
+        assert_eq!(result.n_parts, 4);
+        assert_eq!(result.edge_cut, edge_cut(&graph, &result.part));
+        for block in 0..4 {
+            assert!(result.part.contains(&block));
+        }
+        assert!(result.part.iter().all(|p| (0..4).contains(p)));
+
+        assert_eq!(
+            graph.partition_recursive(3, 0.03, Mode::Eco),
+            Err(PartitionError::NotPowerOfTwo { n_parts: 3 })
+        );
+        assert_eq!(
+            graph.partition_recursive(1, 0.03, Mode::Eco).unwrap().part,
+            [0; 100]
+        );
+
+        // As many blocks as vertices: each vertex gets a block of its own.
+        let mut grid = grid_2d(4, 4);
+        let mut graph = grid.as_graph();
+        let mut part = graph.partition_recursive(16, 0.03, Mode::Eco).unwrap().part;
+        part.sort_unstable();
+        assert_eq!(part, (0..16).collect::<Vec<Idx>>());
+    }
+
+    #[test]
+    fn mode_from_str() {
+        let modes = [
+            Mode::Fast,
+            Mode::Eco,
+            Mode::Strong,
+            Mode::FastSocial,
+            Mode::EcoSocial,
+            Mode::StrongSocial,
+        ];
+        for mode in modes {
+            assert_eq!(mode.to_string().parse::<Mode>(), Ok(mode));
+            assert_eq!(mode.to_string().to_uppercase().parse::<Mode>(), Ok(mode));
+        }
+        assert_eq!("EcoSocial".parse::<Mode>(), Ok(Mode::EcoSocial));
+
+        let error = "eco-social".parse::<Mode>().unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "unknown mode \"eco-social\", expected one of fast, eco, strong, fastsocial, ecosocial, strongsocial"
+        );
+    }
+
+    #[test]
+    fn mode_quality_and_social() {
+        let modes = [
+            (Mode::Fast, kahip_sys::FAST),
+            (Mode::Eco, kahip_sys::ECO),
+            (Mode::Strong, kahip_sys::STRONG),
+            (Mode::FastSocial, kahip_sys::FASTSOCIAL),
+            (Mode::EcoSocial, kahip_sys::ECOSOCIAL),
+            (Mode::StrongSocial, kahip_sys::STRONGSOCIAL),
+        ];
+        for (mode, constant) in modes {
+            assert_eq!(mode as Idx, constant as Idx);
+            assert_eq!(Mode::new(mode.quality(), mode.is_social()), mode);
+        }
+        for quality in [Quality::Fast, Quality::Eco, Quality::Strong] {
+            for social in [false, true] {
+                let mode = Mode::new(quality, social);
+                assert_eq!((mode.quality(), mode.is_social()), (quality, social));
+            }
+        }
+    }
+
+    #[test]
+    fn partition_best_of() {
+        let mut grid = grid_2d(30, 30);
+        let mut graph = grid.as_graph();
+
+        let seeds = [1, 2, 3, 4, 5];
+        let best = graph.partition_best_of(&seeds, 6, 0.03, Mode::Fast);
+        for seed in seeds {
+            let result = graph.partition(6, 0.03, true, seed, Mode::Fast);
+            assert!(best.edge_cut <= result.edge_cut);
+        }
+        assert_eq!(best.edge_cut, edge_cut(&graph, &best.part));
+    }
+
+    #[test]
+    fn partition_imbalance_percent() {
+        let mut grid = grid_2d(20, 20);
+        let mut graph = grid.as_graph();
+
+        let cfg = PartitionConfig::new(4).set_seed(1234);
+        let percent = graph.partition_with(&cfg.clone().set_imbalance(Imbalance::percent(3.0)));
+        let fraction = graph.partition_with(&cfg.set_imbalance(Imbalance::fraction(0.03)));
+        assert_eq!(percent, fraction);
+    }
+
+    #[test]
+    fn version() {
+        assert!(!kahip_version().is_empty());
+    }
+
+    #[test]
+    fn index_overflow() {
+        assert_eq!(checked_idx(5), Ok(5));
+        assert_eq!(checked_idx(Idx::MAX as usize), Ok(Idx::MAX));
+        assert_eq!(
+            checked_idx(Idx::MAX as usize + 1),
+            Err(PartitionError::IndexOverflow {
+                len: Idx::MAX as usize + 1
+            })
+        );
+    }
+
+    #[test]
+    fn partition_single_part() {
+        let mut xadj = vec![0, 2, 5, 7, 9, 12];
+        let mut adjncy = vec![1, 4, 0, 2, 4, 1, 3, 2, 4, 0, 1, 3];
+        let mut graph = Graph::new(&mut xadj, &mut adjncy);
+
+        let result = graph.partition(1, 0.03, true, 1234, Mode::Eco);
+        assert_eq!(result.part, [0; 5]);
+        assert_eq!(result.edge_cut, 0);
+        assert_eq!(result.n_parts, 1);
+
+        let mut part = vec![-1; 5];
+        let edge_cut = graph.partition_into(&mut part, 1, 0.03, true, 1234, Mode::Eco);
+        assert_eq!(part, [0; 5]);
+        assert_eq!(edge_cut, 0);
+    }
+
+    #[test]
+    fn try_partition_into_wrong_length() {
+        let mut xadj = vec![0, 2, 5, 7, 9, 12];
+        let mut adjncy = vec![1, 4, 0, 2, 4, 1, 3, 2, 4, 0, 1, 3];
+        let mut graph = Graph::new(&mut xadj, &mut adjncy);
+
+        let mut part = vec![-1; 4];
+        assert_eq!(
+            graph.try_partition_into(&mut part, &PartitionConfig::new(2)),
+            Err(PartitionError::PartLengthMismatch {
+                expected: 5,
+                got: 4
+            })
+        );
+        assert_eq!(part, [-1; 4]);
+
+        assert_eq!(
+            graph.try_partition(6, 0.03, true, 1234, Mode::Eco),
+            Err(PartitionError::InvalidNParts {
+                n_parts: 6,
+                n_vertices: 5
+            })
+        );
+    }
+
+    #[test]
+    fn partition_empty_graph() {
+        let mut xadj = vec![0];
+        let mut adjncy = vec![];
+        let mut graph = Graph::new(&mut xadj, &mut adjncy);
+
+        // A zero time shows that KaHIP was not called.
+        let (result, elapsed) = graph.partition_timed(&PartitionConfig::new(4));
+        assert_eq!(result.part, []);
+        assert_eq!(result.edge_cut, 0);
+        assert_eq!(result.n_parts, 4);
+        assert_eq!(elapsed, Duration::ZERO);
+
+        assert_eq!(
+            graph.try_partition_with(&PartitionConfig::new(0)),
+            Err(PartitionError::InvalidNParts {
+                n_parts: 0,
+                n_vertices: 0
+            })
+        );
+    }
+
+    #[test]
+    fn partition_without_edges() {
+        let mut xadj = vec![0; 7];
+        let mut adjncy = vec![];
+        let mut vwgt = vec![1, 1, 1, 1, 1, 5];
+        let mut graph = Graph::new(&mut xadj, &mut adjncy);
+
+        let (result, elapsed) = graph.partition_timed(&PartitionConfig::new(3));
+        assert_eq!(result.part, [0, 1, 2, 0, 1, 2]);
+        assert_eq!(result.edge_cut, 0);
+        assert_eq!(elapsed, Duration::ZERO);
+
+        let mut graph = graph.set_vwgt(&mut vwgt);
+        let (result, elapsed) = graph.partition_timed(&PartitionConfig::new(2));
+        assert_eq!(result.part, [0, 1, 0, 1, 0, 1]);
+        assert_eq!(elapsed, Duration::ZERO);
+    }
+
+    #[test]
+    fn num_threads() {
+        set_num_threads(2);
+        #[cfg(feature = "openmp")]
+        assert_eq!(unsafe { crate::m::omp_get_max_threads() }, 2);
+
+        let mut xadj = vec![0, 2, 5, 7, 9, 12];
+        let mut adjncy = vec![1, 4, 0, 2, 4, 1, 3, 2, 4, 0, 1, 3];
+        let mut graph = Graph::new(&mut xadj, &mut adjncy);
+        assert_eq!(graph.partition_default(2).part.len(), 5);
+        set_num_threads(1);
+    }
+
+    #[test]
+    #[should_panic(expected = "the number of threads must be positive")]
+    fn zero_threads() {
+        set_num_threads(0);
+    }
+
+    #[test]
+    fn partition_default() {
+        let mut xadj = vec![0, 2, 5, 7, 9, 12];
+        let mut adjncy = vec![1, 4, 0, 2, 4, 1, 3, 2, 4, 0, 1, 3];
+        let mut graph = Graph::new(&mut xadj, &mut adjncy);
+
+        let result = graph.partition_default(2);
+        assert_eq!(result.part.len(), 5);
+        assert!(result.part.iter().all(|&p| p == 0 || p == 1));
+        assert_eq!(result.edge_cut, edge_cut(&graph, &result.part));
+        assert_eq!(result, graph.partition_with(&PartitionConfig::new(2)));
+    }
+
+    #[test]
+    fn partition_isolated_vertices() {
+        let mut xadj = vec![0, 0, 0, 0];
+        let mut adjncy = vec![];
+        let mut graph = Graph::new(&mut xadj, &mut adjncy);
+        assert_eq!(graph.num_vertices(), 3);
+        assert_eq!(graph.num_edges(), 0);
+
+        let result = graph.partition(2, 0.03, true, 1234, Mode::Strong);
+        assert_eq!(result.part, [0, 1, 0]);
+        assert_eq!(result.edge_cut, 0);
+        assert_eq!(result.n_parts, 2);
+
+        let mut graph = GraphBuf::new(vec![0, 0, 0, 0], Vec::new());
+        assert_eq!(
+            graph
+                .as_graph()
+                .partition_with(&PartitionConfig::new(2))
+                .part,
+            [0, 1, 0]
+        );
+    }
+
+    #[test]
+    fn partition_target_weights() {
+        let mut grid = grid_2d(20, 20);
+        let mut graph = grid.as_graph();
+
+        let targets = [0.5, 0.25, 0.25];
+        let cfg = PartitionConfig::new(3)
+            .set_seed(1234)
+            .set_target_weights(&targets);
+        let result = graph.partition_with(&cfg);
+
+        assert_eq!(result.n_parts, 3);
+        let mut sizes = [0; 3];
+        for &p in &result.part {
+            sizes[p as usize] += 1;
+        }
+        for (size, target) in sizes.into_iter().zip(targets) {
+            assert!(size as f64 <= 1.03 * target * 400.0 + 1.0);
+        }
+        let expected = sizes
+            .into_iter()
+            .zip(targets)
+            .map(|(size, target)| size as f64 / (target * 400.0) - 1.0)
+            .fold(f64::NEG_INFINITY, f64::max);
+        assert_eq!(result.imbalance, expected);
+    }
+
+    #[test]
+    fn partition_invalid_target_weights() {
+        let mut grid = grid_2d(20, 20);
+        let mut graph = grid.as_graph();
+
+        let cfg = PartitionConfig::new(3).set_target_weights(&[0.5, 0.5]);
+        assert_eq!(
+            graph.try_partition_with(&cfg),
+            Err(PartitionError::TargetWeightsLengthMismatch {
+                expected: 3,
+                got: 2
+            })
+        );
+
+        let cfg = PartitionConfig::new(3).set_target_weights(&[0.5, 0.5, 0.0]);
+        assert_eq!(
+            graph.try_partition_with(&cfg),
+            Err(PartitionError::NonPositiveTargetWeight {
+                block: 2,
+                value: 0.0
+            })
+        );
+
+        let cfg = PartitionConfig::new(2).set_target_weights(&[2.0, 1.0]);
+        assert_eq!(
+            graph.try_partition_with(&cfg),
+            Err(PartitionError::TargetWeightsSum { sum: 3.0 })
+        );
+    }
+
+    #[test]
+    fn partition_typed() {
+        let mut grid = grid_2d(10, 10);
+        let mut graph = grid.as_graph();
+
+        let cfg = PartitionConfig::new(4).set_seed(1234);
+        let partition = graph.partition_typed(&cfg);
+
+        assert_eq!(partition.blocks(), 4);
+        assert_eq!(partition.as_slice(), graph.partition_with(&cfg).part);
+    }
+
+    #[test]
+    fn partition_multi_constraint() {
+        let mut grid = grid_2d(10, 10);
+        let mut vwgt: Vec<Idx> = (0..100).collect();
+        let mut vwgt_multi: Vec<Idx> = (0..200).collect();
+
+        let cfg = PartitionConfig::new(4).set_seed(1234);
+        let expected = grid.as_graph().set_vwgt(&mut vwgt).partition_with(&cfg);
+        let mut graph = grid.as_graph().set_vwgt_multi(&mut vwgt, 1);
+        assert_eq!(graph.partition_with(&cfg), expected);
+
+        let mut graph = grid.as_graph().set_vwgt_multi(&mut vwgt_multi, 2);
+        assert_eq!(
+            graph.try_partition_with(&cfg),
+            Err(PartitionError::MultiConstraintUnsupported { ncon: 2 })
+        );
+    }
+
+    #[test]
+    fn clone_to_buf() {
+        let mut grid = grid_2d(6, 6);
+        let mut vwgt: Vec<Idx> = (0..36).map(|v| v % 3 + 1).collect();
+        let mut graph = grid.as_graph().set_vwgt(&mut vwgt);
+
+        let mut copy = graph.clone_to_buf();
+        assert_eq!(copy.xadj(), graph.xadj);
+        assert_eq!(copy.adjncy(), graph.adjncy);
+        assert_eq!(copy.vwgt(), graph.vwgt.as_deref());
+
+        let cfg = PartitionConfig::new(3).set_seed(1234);
+        assert_eq!(
+            copy.as_graph().partition_with(&cfg),
+            graph.partition_with(&cfg)
+        );
+    }
+
+    #[test]
+    #[cfg(kahip_balance_ne)]
+    fn partition_balanced() {
+        // A ring, where every vertex has the same degree.
+        let mut xadj: Vec<Idx> = (0..=30).map(|v| 2 * v).collect();
+        let mut adjncy: Vec<Idx> = (0..30)
+            .flat_map(|v| [(v + 29) % 30, (v + 1) % 30])
+            .collect();
+        let mut graph = Graph::new(&mut xadj, &mut adjncy);
+
+        let result = graph
+            .partition_balanced(4, true, 1234, Mode::Strong)
+            .unwrap();
+        let sizes = crate::block_sizes(&result.part, 4);
+        let (min, max) = (sizes.iter().min().unwrap(), sizes.iter().max().unwrap());
+        assert!(max - min <= 1, "block sizes {sizes:?}");
+        assert_eq!(result.edge_cut, edge_cut(&graph, &result.part));
+
+        assert_eq!(
+            graph.partition_balanced(31, true, 1234, Mode::Strong),
+            Err(PartitionError::InvalidNParts {
+                n_parts: 31,
+                n_vertices: 30
+            })
+        );
+    }
+
+    #[test]
+    fn try_set_weights() {
+        let mut grid = grid_2d(3, 3);
+        let mut vwgt = vec![1; 8];
+        let mut adjwgt = vec![1; 25];
+        let graph = grid.as_graph();
+        assert_eq!(
+            graph.try_set_vwgt(&mut vwgt).unwrap_err(),
+            GraphError::VwgtLengthMismatch {
+                expected: 9,
+                got: 8
+            }
+        );
+        let graph = grid.as_graph();
+        assert_eq!(
+            graph.try_set_adjwgt(&mut adjwgt).unwrap_err(),
+            GraphError::AdjwgtLengthMismatch {
+                expected: 24,
+                got: 25
+            }
+        );
+
+        let mut vwgt = vec![2; 9];
+        let mut adjwgt = vec![3; 24];
+        let graph = grid
+            .as_graph()
+            .try_set_vwgt(&mut vwgt)
+            .unwrap()
+            .try_set_adjwgt(&mut adjwgt)
+            .unwrap();
+        assert_eq!(graph.vwgt.as_deref(), Some(&[2; 9][..]));
+        assert_eq!(graph.adjwgt.as_deref(), Some(&[3; 24][..]));
+    }
+
+    #[test]
+    #[should_panic]
+    fn set_vwgt_multi_wrong_length() {
+        let mut grid = grid_2d(10, 10);
+        let mut vwgt = vec![1; 200];
+        let _ = grid.as_graph().set_vwgt_multi(&mut vwgt, 3);
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn partition_captured() {
+        let mut grid = grid_2d(10, 10);
+        let mut graph = grid.as_graph();
+
+        let cfg = PartitionConfig::new(4).set_seed(1234);
+        let (result, output) = graph.partition_captured(&cfg).unwrap();
+
+        assert_eq!(result, graph.partition_with(&cfg));
+        assert_eq!(output, "");
+    }
+
+    #[test]
+    fn partition_deterministic() {
+        let mut grid = grid_2d(20, 20);
+
+        for mode in [Mode::Fast, Mode::Eco, Mode::FastSocial] {
+            let first = grid
+                .as_graph()
+                .partition_deterministic(4, 0.03, mode)
+                .unwrap();
+            let second = grid
+                .as_graph()
+                .partition_deterministic(4, 0.03, mode)
+                .unwrap();
+            assert_eq!(first, second);
+        }
+
+        assert!(grid
+            .as_graph()
+            .partition_deterministic(0, 0.03, Mode::Eco)
+            .is_err());
+    }
+
+    #[test]
+    fn partition_timed() {
+        let mut grid = grid_2d(100, 100);
+        let mut graph = grid.as_graph();
+
+        let cfg = PartitionConfig::new(8).set_seed(1234);
+        let (result, elapsed) = graph.partition_timed(&cfg);
+
+        assert_eq!(result, graph.partition_with(&cfg));
+        assert!(!elapsed.is_zero());
+    }
+
+    #[test]
+    fn partition_fixed_vertices() {
+        let mut grid = grid_2d(10, 10);
+        let mut fixed = vec![-1; 100];
+        fixed[0] = 1;
+        fixed[99] = 0;
+        let mut graph = grid.as_graph().set_fixed_vertices(&fixed);
+
+        let result = graph.partition(2, 0.03, true, 1234, Mode::Eco);
+
+        assert_eq!(result.part[0], 1);
+        assert_eq!(result.part[99], 0);
+        assert_eq!(result.edge_cut, edge_cut(&graph, &result.part));
+
+        fixed[99] = 2;
+        let mut graph = grid.as_graph().set_fixed_vertices(&fixed);
+        assert_eq!(
+            graph.try_partition_with(&PartitionConfig::new(2)),
+            Err(PartitionError::FixedBlockOutOfRange {
+                vertex: 99,
+                block: 2
+            })
+        );
+    }
+
+    #[test]
+    fn partition_fixed_vertices_with_targets() {
+        let mut grid = grid_2d(10, 10);
+        let mut fixed = vec![-1; 100];
+        fixed[0] = 1;
+        fixed[99] = 0;
+        let mut graph = grid.as_graph().set_fixed_vertices(&fixed);
+
+        let cfg = PartitionConfig::new(2).set_target_weights(&[0.75, 0.25]);
+        assert_eq!(
+            graph.try_partition_with(&cfg),
+            Err(PartitionError::FixedWithTargetWeights)
+        );
+
+        // Equal targets leave the blocks interchangeable.
+        let cfg = PartitionConfig::new(2)
+            .set_seed(1234)
+            .set_target_weights(&[0.5, 0.5]);
+        let result = graph.partition_with(&cfg);
+        assert_eq!(result.part[0], 1);
+        assert_eq!(result.part[99], 0);
+    }
+
+    #[test]
+    fn partition_into() {
+        let mut grid = grid_2d(10, 10);
+        let mut graph = grid.as_graph();
+
+        let result = graph.partition(4, 0.03, true, 1234, Mode::Eco);
+        let mut part = vec![-1; 100];
+        let edge_cut = graph.partition_into(&mut part, 4, 0.03, true, 1234, Mode::Eco);
+
+        assert_eq!(part, result.part);
+        assert_eq!(edge_cut, result.edge_cut);
+    }
+
+    #[test]
+    #[should_panic]
+    fn partition_into_wrong_length() {
+        let mut grid = grid_2d(10, 10);
+        let mut part = vec![0; 99];
+        grid.as_graph()
+            .partition_into(&mut part, 4, 0.03, true, 1234, Mode::Eco);
+    }
+
+    #[test]
+    fn node_separator() {
+        let mut xadj = vec![0, 2, 5, 7, 9, 12];
+        let mut adjncy = vec![1, 4, 0, 2, 4, 1, 3, 2, 4, 0, 1, 3];
+        let mut graph = Graph::new(&mut xadj, &mut adjncy);
+
+        let (label, size) = graph
+            .node_separator(2, 0.03, true, 1234, Mode::Eco)
+            .unwrap();
+
+        assert_eq!(label.len(), 5);
+        assert_eq!(label.iter().filter(|&&l| l == 2).count(), size as usize);
+        assert!(0 < size && size <= 2);
+        assert!(label.iter().all(|&l| (0..=2).contains(&l)));
+        for v in 0..5 {
+            for &u in &adjncy[xadj[v] as usize..xadj[v + 1] as usize] {
+                let (a, b) = (label[v], label[u as usize]);
+                assert!(a == b || a == 2 || b == 2);
+            }
+        }
+        let mut graph = Graph::new(&mut xadj, &mut adjncy);
+        assert_eq!(
+            graph.node_separator(2, 0.03, true, 1234, Mode::Eco),
+            Ok((label, size))
+        );
+        assert_eq!(
+            graph.node_separator(6, 0.03, true, 1234, Mode::Eco),
+            Err(PartitionError::InvalidNParts {
+                n_parts: 6,
+                n_vertices: 5
+            })
+        );
+    }
+
+    #[test]
+    fn nested_dissection() {
+        let mut xadj = vec![0, 2, 5, 7, 9, 12];
+        let mut adjncy = vec![1, 4, 0, 2, 4, 1, 3, 2, 4, 0, 1, 3];
+
+        let mut ordering = Graph::new(&mut xadj, &mut adjncy)
+            .nested_dissection(true, 1234, Mode::Eco)
+            .unwrap();
+
+        ordering.sort();
+        assert_eq!(ordering, [0, 1, 2, 3, 4]);
+
+        let mut xadj = vec![0];
+        let ordering = Graph::new(&mut xadj, &mut [])
+            .nested_dissection(true, 1234, Mode::Eco)
+            .unwrap();
+        assert!(ordering.is_empty());
+    }
+
+    #[test]
+    fn edge_partition() {
+        let mut xadj = vec![0, 2, 5, 7, 9, 12];
+        let mut adjncy = vec![1, 4, 0, 2, 4, 1, 3, 2, 4, 0, 1, 3];
+
+        let mut graph = Graph::new(&mut xadj, &mut adjncy);
+        let (part, _) = graph
+            .edge_partition(2, 0.03, true, 1234, Mode::Eco)
+            .unwrap();
+
+        assert_eq!(part.len(), 12);
+        assert!(part.iter().all(|&p| p == 0 || p == 1));
+
+        assert_eq!(
+            graph.edge_partition(0, 0.03, true, 1234, Mode::Eco),
+            Err(PartitionError::InvalidNParts {
+                n_parts: 0,
+                n_vertices: 5
+            })
+        );
+        assert_eq!(
+            graph.edge_partition(2, -1.0, true, 1234, Mode::Eco),
+            Err(PartitionError::InvalidImbalance { imbalance: -1.0 })
+        );
+    }
+
+    #[test]
+    fn process_mapping() {
+        let mut grid = grid_2d(8, 8);
+        let mut graph = grid.as_graph();
+
+        let result = graph
+            .process_mapping(
+                &[2, 2],
+                &[1, 10],
+                4,
+                0.03,
+                true,
+                1234,
+                Mode::Eco,
+                MapMode::Multisection,
+            )
+            .unwrap();
+
+        assert_eq!(result.part.len(), 64);
+        assert!(result.part.iter().all(|&p| (0..4).contains(&p)));
+        assert_eq!(result.edge_cut, edge_cut(&graph, &result.part));
+        assert!(result.edge_cut >= 0);
+        // Each cut edge costs between the smallest and the largest distance.
+        assert!(result.edge_cut <= result.qap);
+        assert!(result.qap <= 10 * result.edge_cut);
+    }
+
+    #[test]
+    fn process_mapping_wrong_hierarchy() {
+        let mut grid = grid_2d(8, 8);
+        let mut graph = grid.as_graph();
+        let mut map = |hierarchy: &[Idx], distance: &[Idx], n_parts, imbalance| {
+            graph
+                .process_mapping(
+                    hierarchy,
+                    distance,
+                    n_parts,
+                    imbalance,
+                    true,
+                    1234,
+                    Mode::Eco,
+                    MapMode::Multisection,
+                )
+                .unwrap_err()
+        };
+
+        assert_eq!(
+            map(&[2, 2], &[1, 10], 8, 0.03),
+            PartitionError::InvalidHierarchy { n_parts: 8 }
+        );
+        assert_eq!(
+            map(&[-2, -2], &[1, 10], 4, 0.03),
+            PartitionError::InvalidHierarchy { n_parts: 4 }
+        );
+        assert_eq!(
+            map(&[2, 2], &[1], 4, 0.03),
+            PartitionError::HierarchyLengthMismatch {
+                hierarchy: 2,
+                distance: 1
+            }
+        );
+        assert_eq!(
+            map(&[], &[], 1, -1.0),
+            PartitionError::InvalidImbalance { imbalance: -1.0 }
+        );
+    }
+
+    #[cfg(feature = "logging")]
+    #[test]
+    fn partition_logs_summary() {
+        use std::sync::Mutex;
+
+        static MESSAGES: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+        struct Capture;
+
+        impl log::Log for Capture {
+            fn enabled(&self, _: &log::Metadata) -> bool {
+                true
+            }
+
+            fn log(&self, record: &log::Record) {
+                MESSAGES.lock().unwrap().push(record.args().to_string());
+            }
+
+            fn flush(&self) {}
+        }
+
+        static LOGGER: Capture = Capture;
+        log::set_logger(&LOGGER).unwrap();
+        log::set_max_level(log::LevelFilter::Trace);
+
+        let mut grid = grid_2d(10, 10);
+        grid.as_graph().partition(4, 0.03, true, 1234, Mode::Eco);
+
+        let messages = MESSAGES.lock().unwrap();
+        assert!(messages
+            .iter()
+            .any(|m| m.starts_with("kaffpa: 100 vertices, 180 edges, 4 blocks, mode Eco")));
+    }
+
+    /// Meant to be run with `--release` as well, where a dangling vertex
+    /// count used to show up as garbage partitions.
+    #[test]
+    fn repeated_partition() {
+        let mut grid = grid_2d(20, 20);
+        let mut graph = grid.as_graph();
+
+        let first = graph.partition(4, 0.03, true, 1234, Mode::Eco);
+        assert_eq!(first.part.len(), 400);
+        for _ in 0..10 {
+            assert_eq!(graph.partition(4, 0.03, true, 1234, Mode::Eco), first);
+        }
+    }
+
+    const _: () = {
+        const fn assert_send<T: Send>() {}
+        assert_send::<Graph<'static>>();
+        assert_send::<crate::GraphBuf>();
+    };
+
+    #[test]
+    fn partition_on_threads() {
+        let mut grid = grid_2d(20, 20);
+        let expected = grid.as_graph().partition(4, 0.03, true, 1234, Mode::Eco);
+
+        let results: Vec<_> = std::thread::scope(|scope| {
+            let handles: Vec<_> = (0..2)
+                .map(|_| {
+                    let mut grid = grid_2d(20, 20);
+                    scope.spawn(move || {
+                        let mut graph = grid.as_graph();
+                        graph.partition(4, 0.03, true, 1234, Mode::Eco)
+                    })
+                })
+                .collect();
+            handles.into_iter().map(|h| h.join().unwrap()).collect()
+        });
+        for result in results {
+            assert_eq!(result, expected);
+        }
     }
 }
@@ -0,0 +1,342 @@
+//! Reading and writing graphs in the METIS format, shared by KaHIP, and
+//! partitions in the format of KaHIP's programs.
+
+use crate::{GraphBuf, Idx};
+use std::io::{self, BufRead, BufWriter, Write};
+
+/// Returns an [`io::Error`] for a malformed file.
+pub(crate) fn invalid(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+/// Parses the next integer of a line.
+pub(crate) fn next_int<'l>(
+    tokens: &mut impl Iterator<Item = &'l str>,
+    line: usize,
+    what: &str,
+) -> io::Result<Idx> {
+    let token = tokens
+        .next()
+        .ok_or_else(|| invalid(format!("line {line}: missing {what}")))?;
+    token
+        .parse()
+        .map_err(|_| invalid(format!("line {line}: invalid {what} {token:?}")))
+}
+
+/// Parses the next integer of a line, which must be a non-negative count.
+pub(crate) fn next_count<'l>(
+    tokens: &mut impl Iterator<Item = &'l str>,
+    line: usize,
+    what: &str,
+) -> io::Result<Idx> {
+    let count = next_int(tokens, line, what)?;
+    if count < 0 {
+        return Err(invalid(format!("line {line}: negative {what} {count}")));
+    }
+    Ok(count)
+}
+
+/// The largest number of elements reserved from the counts of a header,
+/// which cannot be trusted: larger arrays grow as they are read.
+pub(crate) const MAX_RESERVE: usize = 1 << 20;
+
+impl GraphBuf {
+    /// Reads a graph in the METIS format, as used by KaHIP's programs.
+    ///
+    /// The header line holds the number of vertices and edges, optionally
+    /// followed by a format number and the number of weights per vertex. In
+    /// the format number, a 1 as last digit means that edges are weighted,
+    /// as second to last digit that vertices are weighted, and as third to
+    /// last digit that vertices have a size, which is ignored. Then each
+    /// vertex has a line with its size and weights if any, followed by its
+    /// neighbors, each followed by the weight of the edge if any. Vertex ids
+    /// start at 1 in the file and at 0 in the returned graph. Lines starting
+    /// with `%` are comments.
+    ///
+    /// With several weights per vertex, they are stored vertex by vertex, as
+    /// in [`Graph::set_vwgt_multi`](crate::Graph::set_vwgt_multi).
+    pub fn read_metis<R: BufRead>(reader: R) -> io::Result<GraphBuf> {
+        let mut lines = reader
+            .lines()
+            .enumerate()
+            .map(|(i, line)| (i + 1, line))
+            .filter(|(_, line)| !matches!(line, Ok(line) if line.starts_with('%')));
+
+        let (number, header) = lines
+            .next()
+            .ok_or_else(|| invalid("missing header".to_string()))?;
+        let header = header?;
+        let mut tokens = header.split_whitespace();
+        let n_vertices = next_count(&mut tokens, number, "number of vertices")?;
+        let n_edges = next_count(&mut tokens, number, "number of edges")?;
+        let n_neighbors = n_edges
+            .checked_mul(2)
+            .ok_or_else(|| invalid(format!("line {number}: too many edges {n_edges}")))?;
+        let fmt = match tokens.next() {
+            Some(fmt) if fmt.len() <= 3 && fmt.bytes().all(|b| b == b'0' || b == b'1') => {
+                format!("{fmt:0>3}")
+            }
+            Some(fmt) => return Err(invalid(format!("line {number}: invalid format {fmt:?}"))),
+            None => "000".to_string(),
+        };
+        let has_vsize = &fmt[0..1] == "1";
+        let has_vwgt = &fmt[1..2] == "1";
+        let has_adjwgt = &fmt[2..3] == "1";
+        let ncon = match tokens.next() {
+            Some(_) if !has_vwgt => {
+                return Err(invalid(format!(
+                    "line {number}: number of vertex weights without vertex weights"
+                )))
+            }
+            Some(token) => token
+                .parse()
+                .ok()
+                .filter(|&ncon: &usize| ncon > 0)
+                .ok_or_else(|| {
+                    invalid(format!(
+                        "line {number}: invalid number of vertex weights {token:?}"
+                    ))
+                })?,
+            None => 1,
+        };
+
+        let mut xadj = Vec::with_capacity((n_vertices as usize).min(MAX_RESERVE) + 1);
+        let mut adjncy = Vec::with_capacity((n_neighbors as usize).min(MAX_RESERVE));
+        let mut vwgt = Vec::new();
+        let mut adjwgt = Vec::new();
+
+        xadj.push(0);
+        for _ in 0..n_vertices {
+            let (number, line) = lines
+                .next()
+                .ok_or_else(|| invalid(format!("expected {n_vertices} vertices")))?;
+            let line = line?;
+            let mut tokens = line.split_whitespace().peekable();
+            if has_vsize {
+                next_int(&mut tokens, number, "vertex size")?;
+            }
+            if has_vwgt {
+                for _ in 0..ncon {
+                    vwgt.push(next_int(&mut tokens, number, "vertex weight")?);
+                }
+            }
+            while tokens.peek().is_some() {
+                let neighbor = next_int(&mut tokens, number, "neighbor")?;
+                if !(1..=n_vertices).contains(&neighbor) {
+                    return Err(invalid(format!(
+                        "line {number}: neighbor {neighbor} is not a vertex"
+                    )));
+                }
+                adjncy.push(neighbor - 1);
+                if has_adjwgt {
+                    adjwgt.push(next_int(&mut tokens, number, "edge weight")?);
+                }
+            }
+            xadj.push(adjncy.len() as Idx);
+        }
+
+        if adjncy.len() != n_neighbors as usize {
+            return Err(invalid(format!(
+                "expected {n_edges} edges, found {} neighbors",
+                adjncy.len()
+            )));
+        }
+
+        let mut graph = GraphBuf::new(xadj, adjncy);
+        if has_vwgt {
+            graph.vwgt = Some(vwgt);
+            graph.ncon = ncon;
+        }
+        if has_adjwgt {
+            graph = graph.set_adjwgt(adjwgt);
+        }
+        Ok(graph)
+    }
+
+    /// Writes the graph in the METIS format, as used by KaHIP's programs.
+    ///
+    /// The format number and the number of weights per vertex are only
+    /// written when needed. See [`GraphBuf::read_metis`] for a description of
+    /// the format.
+    pub fn write_metis<W: Write>(&self, writer: W) -> io::Result<()> {
+        let mut writer = BufWriter::new(writer);
+
+        let n_vertices = self.xadj.len() - 1;
+        write!(writer, "{} {}", n_vertices, self.adjncy.len() / 2)?;
+        match (self.vwgt.is_some(), self.adjwgt.is_some()) {
+            (true, true) => write!(writer, " 11")?,
+            (true, false) => write!(writer, " 10")?,
+            (false, true) => write!(writer, " 1")?,
+            (false, false) => {}
+        }
+        if self.vwgt.is_some() && self.ncon > 1 {
+            write!(writer, " {}", self.ncon)?;
+        }
+        writeln!(writer)?;
+
+        for (v, w) in self.xadj.windows(2).enumerate() {
+            let mut sep = "";
+            if let Some(vwgt) = &self.vwgt {
+                for weight in &vwgt[v * self.ncon..(v + 1) * self.ncon] {
+                    write!(writer, "{sep}{weight}")?;
+                    sep = " ";
+                }
+            }
+            for k in w[0] as usize..w[1] as usize {
+                write!(writer, "{sep}{}", self.adjncy[k] + 1)?;
+                sep = " ";
+                if let Some(adjwgt) = &self.adjwgt {
+                    write!(writer, " {}", adjwgt[k])?;
+                }
+            }
+            writeln!(writer)?;
+        }
+        writer.flush()
+    }
+}
+
+/// Writes a partition in the format of KaHIP's programs: the block of each
+/// vertex, one per line.
+pub fn write_partition<W: Write>(part: &[Idx], writer: W) -> io::Result<()> {
+    let mut writer = BufWriter::new(writer);
+    for block in part {
+        writeln!(writer, "{block}")?;
+    }
+    writer.flush()
+}
+
+/// Reads a partition in the format of KaHIP's programs: the block of each
+/// vertex, one per line. Blank lines are ignored.
+pub fn read_partition<R: BufRead>(reader: R) -> io::Result<Vec<Idx>> {
+    let mut part = Vec::new();
+    for (i, line) in reader.lines().enumerate() {
+        let line = line?;
+        let mut tokens = line.split_whitespace().peekable();
+        if tokens.peek().is_none() {
+            continue;
+        }
+        let block = next_int(&mut tokens, i + 1, "block")?;
+        if block < 0 || tokens.next().is_some() {
+            return Err(invalid(format!("line {}: invalid block {line:?}", i + 1)));
+        }
+        part.push(block);
+    }
+    Ok(part)
+}
+
+#[cfg(test)]
+mod tests {
+
+    use crate::{read_partition, write_partition, GraphBuf};
+
+    /// The sample graph with vertex and edge weights.
+    const WEIGHTED: &str = "\
+% The sample graph.
+5 6 11
+1 2 1 5 2
+2 1 1 3 3 5 4
+3 2 3 4 5
+% Comments may appear anywhere.
+4 3 5 5 6
+5 1 2 2 4 4 6
+";
+
+    #[test]
+    fn read_metis() {
+        let graph = GraphBuf::read_metis(WEIGHTED.as_bytes()).unwrap();
+
+        assert_eq!(graph.xadj(), [0, 2, 5, 7, 9, 12]);
+        assert_eq!(graph.adjncy(), [1, 4, 0, 2, 4, 1, 3, 2, 4, 0, 1, 3]);
+        assert_eq!(graph.vwgt(), Some(&[1, 2, 3, 4, 5][..]));
+        assert_eq!(
+            graph.adjwgt(),
+            Some(&[1, 2, 1, 3, 4, 3, 5, 5, 6, 2, 4, 6][..])
+        );
+    }
+
+    #[test]
+    fn read_metis_unweighted() {
+        // Vertex 3 has no neighbor.
+        let graph = GraphBuf::read_metis("3 1\n2\n1\n\n".as_bytes()).unwrap();
+
+        assert_eq!(graph.xadj(), [0, 1, 2, 2]);
+        assert_eq!(graph.adjncy(), [1, 0]);
+        assert_eq!(graph.vwgt(), None);
+        assert_eq!(graph.adjwgt(), None);
+    }
+
+    #[test]
+    fn read_metis_multi_constraint() {
+        let graph = GraphBuf::read_metis("2 1 110 2\n1 1 2 2\n1 3 4 1\n".as_bytes()).unwrap();
+
+        assert_eq!(graph.adjncy(), [1, 0]);
+        assert_eq!(graph.vwgt(), Some(&[1, 2, 3, 4][..]));
+        assert_eq!(graph.ncon, 2);
+    }
+
+    #[test]
+    fn write_metis() {
+        let graph = GraphBuf::read_metis(WEIGHTED.as_bytes()).unwrap();
+        let mut file = Vec::new();
+        graph.write_metis(&mut file).unwrap();
+
+        assert_eq!(
+            String::from_utf8(file.clone()).unwrap(),
+            "5 6 11\n1 2 1 5 2\n2 1 1 3 3 5 4\n3 2 3 4 5\n4 3 5 5 6\n5 1 2 2 4 4 6\n"
+        );
+        assert_eq!(GraphBuf::read_metis(&file[..]).unwrap(), graph);
+    }
+
+    #[test]
+    fn write_metis_round_trip() {
+        let sample = GraphBuf::new(
+            vec![0, 2, 5, 7, 9, 12],
+            vec![1, 4, 0, 2, 4, 1, 3, 2, 4, 0, 1, 3],
+        );
+        let multi = GraphBuf::read_metis("2 1 110 2\n1 1 2 2\n1 3 4 1\n".as_bytes()).unwrap();
+        let isolated = GraphBuf::new(vec![0, 1, 2, 2], vec![1, 0]).set_vwgt(vec![3, 1, 2]);
+
+        for graph in [sample, multi, isolated] {
+            let mut file = Vec::new();
+            graph.write_metis(&mut file).unwrap();
+            assert_eq!(GraphBuf::read_metis(&file[..]).unwrap(), graph);
+        }
+    }
+
+    #[test]
+    fn read_metis_invalid() {
+        for file in [
+            "",
+            "2\n",
+            "2 1 12\n2\n1\n",
+            "2 1\n2\n",
+            "2 1\n3\n1\n",
+            "2 1\n2\n1 x\n",
+            "2 1 1\n2\n1 1\n",
+            "2 2\n2\n1\n",
+            "-1 0\n",
+            "2 -1\n2\n1\n",
+            "9223372036854775807 0\n",
+            "1 9223372036854775807\n",
+        ] {
+            assert!(GraphBuf::read_metis(file.as_bytes()).is_err(), "{file:?}");
+        }
+    }
+
+    #[test]
+    fn partition_round_trip() {
+        let part = [0, 0, 1, 1, 0];
+
+        let mut file = Vec::new();
+        write_partition(&part, &mut file).unwrap();
+        assert_eq!(String::from_utf8(file.clone()).unwrap(), "0\n0\n1\n1\n0\n");
+        assert_eq!(read_partition(&file[..]).unwrap(), part);
+
+        assert_eq!(read_partition("1\n\n0\n".as_bytes()).unwrap(), [1, 0]);
+        for invalid in ["0\nx\n", "0\n-1\n", "0 1\n"] {
+            let error = read_partition(invalid.as_bytes()).unwrap_err();
+            assert_eq!(error.kind(), std::io::ErrorKind::InvalidData);
+            assert!(error.to_string().starts_with("line "));
+        }
+    }
+}
@@ -0,0 +1,141 @@
+//! Conversions to and from the graphs of the [`metis`] crate.
+
+use crate::{GraphBuf, GraphError, Idx};
+
+/// Converts an index or weight between the index types of the two crates.
+fn convert<T, U>(values: &[T]) -> Result<Vec<U>, GraphError>
+where
+    T: Copy + Into<i64>,
+    U: TryFrom<T>,
+{
+    values
+        .iter()
+        .map(|&value| {
+            U::try_from(value).map_err(|_| GraphError::IndexOverflow {
+                value: value.into(),
+            })
+        })
+        .collect()
+}
+
+/// A graph that owns its arrays in the index type of the [`metis`] crate.
+///
+/// The graphs of the `metis` crate borrow their arrays, so this holds them
+/// for [`MetisGraphBuf::as_metis_graph`], the same way a [`GraphBuf`] holds
+/// the arrays of a [`Graph`](crate::Graph). See [`GraphBuf::to_metis`] and
+/// [`GraphBuf::from_metis`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct MetisGraphBuf {
+    xadj: Vec<::metis::Idx>,
+    adjncy: Vec<::metis::Idx>,
+    vwgt: Option<Vec<::metis::Idx>>,
+    ncon: usize,
+    adjwgt: Option<Vec<::metis::Idx>>,
+}
+
+impl MetisGraphBuf {
+    /// Borrows this graph as a graph of the [`metis`] crate, to be split into
+    /// `n_parts` blocks.
+    pub fn as_metis_graph(
+        &self,
+        n_parts: ::metis::Idx,
+    ) -> Result<::metis::Graph<'_>, ::metis::InvalidGraphError> {
+        let mut graph =
+            ::metis::Graph::new(self.ncon as ::metis::Idx, n_parts, &self.xadj, &self.adjncy)?;
+        if let Some(vwgt) = &self.vwgt {
+            graph = graph.set_vwgt(vwgt);
+        }
+        if let Some(adjwgt) = &self.adjwgt {
+            graph = graph.set_adjwgt(adjwgt);
+        }
+        Ok(graph)
+    }
+
+    /// The adjency structure of the graph (part 1).
+    pub fn xadj(&self) -> &[::metis::Idx] {
+        &self.xadj
+    }
+
+    /// The adjency structure of the graph (part 2).
+    pub fn adjncy(&self) -> &[::metis::Idx] {
+        &self.adjncy
+    }
+
+    /// The computational weights of the vertices, if any.
+    pub fn vwgt(&self) -> Option<&[::metis::Idx]> {
+        self.vwgt.as_deref()
+    }
+
+    /// The weights of the edges, if any.
+    pub fn adjwgt(&self) -> Option<&[::metis::Idx]> {
+        self.adjwgt.as_deref()
+    }
+}
+
+impl GraphBuf {
+    /// Converts this graph to the index type of the [`metis`] crate, to
+    /// partition the same graph with METIS.
+    ///
+    /// Returns an error if an index or a weight does not fit, e.g. with the
+    /// `idx64` feature and a METIS built with 32-bit indices.
+    pub fn to_metis(&self) -> Result<MetisGraphBuf, GraphError> {
+        Ok(MetisGraphBuf {
+            xadj: convert(&self.xadj)?,
+            adjncy: convert(&self.adjncy)?,
+            vwgt: self.vwgt.as_deref().map(convert).transpose()?,
+            ncon: self.ncon,
+            adjwgt: self.adjwgt.as_deref().map(convert).transpose()?,
+        })
+    }
+
+    /// Converts a graph in the index type of the [`metis`] crate, see
+    /// [`GraphBuf::to_metis`].
+    pub fn from_metis(graph: &MetisGraphBuf) -> Result<GraphBuf, GraphError> {
+        Ok(GraphBuf {
+            xadj: convert::<_, Idx>(&graph.xadj)?,
+            adjncy: convert(&graph.adjncy)?,
+            vwgt: graph.vwgt.as_deref().map(convert).transpose()?,
+            ncon: graph.ncon,
+            adjwgt: graph.adjwgt.as_deref().map(convert).transpose()?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use crate::testgraphs::grid_2d;
+    use crate::{GraphBuf, PartitionConfig};
+
+    #[test]
+    fn metis_round_trip() {
+        let mut graph = grid_2d(10, 10);
+
+        let converted = graph.to_metis().unwrap();
+        assert_eq!(converted.xadj().len(), 101);
+        assert_eq!(converted.adjwgt(), None);
+        let mut round_trip = GraphBuf::from_metis(&converted).unwrap();
+        assert_eq!(round_trip, graph);
+
+        // The same graph on both sides of the conversion.
+        let cfg = PartitionConfig::new(4).set_seed(1234);
+        assert_eq!(
+            round_trip.as_graph().partition_with(&cfg),
+            graph.as_graph().partition_with(&cfg)
+        );
+        let xadj: Vec<::metis::Idx> = graph.xadj().iter().map(|&i| i as _).collect();
+        let adjncy: Vec<::metis::Idx> = graph.adjncy().iter().map(|&i| i as _).collect();
+        let mut direct = vec![0; 100];
+        ::metis::Graph::new(1, 4, &xadj, &adjncy)
+            .unwrap()
+            .part_kway(&mut direct)
+            .unwrap();
+        let mut part = vec![0; 100];
+        converted
+            .as_metis_graph(4)
+            .unwrap()
+            .part_kway(&mut part)
+            .unwrap();
+        assert_eq!(part, direct);
+    }
+}
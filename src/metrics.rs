@@ -0,0 +1,416 @@
+//! Quality metrics of a partition.
+
+pub mod csr;
+#[cfg(feature = "rayon")]
+mod par;
+
+pub use csr::{achieved_imbalance, balance, block_sizes, block_weights, is_balanced};
+#[cfg(feature = "rayon")]
+pub use par::{par_communication_volume, par_edge_cut};
+
+use crate::{GraphRef, Idx};
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt;
+
+/// Returns the total communication volume of a partition.
+///
+/// `graph` is a [`GraphRef`], or a shared borrow of a [`Graph`](crate::Graph)
+/// or a [`GraphBuf`](crate::GraphBuf).
+///
+/// Each vertex is counted once for every block, other than its own, that
+/// holds at least one of its neighbors. This is the total volume over all
+/// blocks, i.e. the amount of data sent when every vertex sends its value to
+/// each remote block that needs it. Vertex and edge weights are ignored.
+///
+/// Unlike the edge cut, a vertex with several neighbors in the same remote
+/// block only counts once.
+///
+/// See [`csr::communication_volume`] for the same metric on plain arrays.
+///
+/// # Panics
+///
+/// This function panics if the length of `part` is different than the number
+/// of vertices.
+pub fn communication_volume<'g>(graph: impl Into<GraphRef<'g>>, part: &[Idx]) -> Idx {
+    let graph = graph.into();
+    csr::communication_volume(graph.xadj, graph.adjncy, part)
+}
+
+/// Returns the edge cut of a partition, that is the total weight of the edges
+/// between different blocks.
+///
+/// `graph` is a [`GraphRef`], or a shared borrow of a [`Graph`](crate::Graph)
+/// or a [`GraphBuf`](crate::GraphBuf).
+///
+/// Each edge counts once, although it is stored in both directions. Edges
+/// have a weight of 1 if the graph has no edge weights.
+///
+/// See [`csr::edge_cut`] for the same metric on plain arrays.
+///
+/// # Panics
+///
+/// This function panics if the length of `part` is different than the number
+/// of vertices.
+pub fn edge_cut<'g>(graph: impl Into<GraphRef<'g>>, part: &[Idx]) -> Idx {
+    let graph = graph.into();
+    csr::edge_cut(graph.xadj, graph.adjncy, graph.adjwgt, part)
+}
+
+/// Returns the contribution of each block to the edge cut, that is the total
+/// weight of the cut edges incident to each block.
+///
+/// `graph` is a [`GraphRef`], or a shared borrow of a [`Graph`](crate::Graph)
+/// or a [`GraphBuf`](crate::GraphBuf).
+///
+/// A cut edge is incident to two blocks and counts for both, so the sum of
+/// the result is twice the [`edge_cut`]. Edges have a weight of 1 if the
+/// graph has no edge weights.
+///
+/// # Panics
+///
+/// This function panics if:
+/// - the length of `part` is different than the number of vertices, or
+/// - an element of `part` is not in `0..n_parts`.
+pub fn per_block_cut<'g>(graph: impl Into<GraphRef<'g>>, part: &[Idx], n_parts: Idx) -> Vec<Idx> {
+    let graph = graph.into();
+    assert_eq!(part.len(), graph.xadj.len() - 1);
+
+    let mut cuts = vec![0; n_parts as usize];
+    for v in 0..part.len() {
+        cuts[part[v] as usize] += csr::vertex_cut(graph.xadj, graph.adjncy, graph.adjwgt, part, v);
+    }
+    cuts
+}
+
+/// Returns the modularity of a partition, which measures how much denser the
+/// blocks are than in a random graph with the same degrees.
+///
+/// `graph` is a [`GraphRef`], or a shared borrow of a [`Graph`](crate::Graph)
+/// or a [`GraphBuf`](crate::GraphBuf).
+///
+/// This is `Q = 1/(2m) Σ_ij (A_ij - k_i k_j / (2m)) δ(c_i, c_j)`, where `A` is
+/// the adjacency matrix, weighted by the edge weights if any, `k_i` the
+/// weighted degree of vertex `i`, `m` the total edge weight and `c_i` the
+/// block of `i`. Equivalently, it sums over the blocks the fraction of the
+/// edge weight inside the block, minus the square of the fraction of the
+/// degrees in the block. It lies between -1/2 and 1, and is zero when all
+/// vertices are in the same block. A graph without edges has a modularity of
+/// zero.
+///
+/// # Panics
+///
+/// This function panics if:
+/// - the length of `part` is different than the number of vertices, or
+/// - an element of `part` is not in `0..n_parts`.
+pub fn modularity<'g>(graph: impl Into<GraphRef<'g>>, part: &[Idx], n_parts: Idx) -> f64 {
+    let graph = graph.into();
+    assert_eq!(part.len(), graph.xadj.len() - 1);
+
+    // Twice the internal edge weight and the total degree of each block.
+    let mut internal = vec![0i64; n_parts as usize];
+    let mut degrees = vec![0i64; n_parts as usize];
+    for v in 0..part.len() {
+        let block = part[v] as usize;
+        for k in graph.xadj[v] as usize..graph.xadj[v + 1] as usize {
+            let w = graph.adjwgt.map_or(1, |adjwgt| adjwgt[k] as i64);
+            degrees[block] += w;
+            if part[graph.adjncy[k] as usize] == part[v] {
+                internal[block] += w;
+            }
+        }
+    }
+    let total: i64 = degrees.iter().sum();
+    if total == 0 {
+        return 0.0;
+    }
+    let total = total as f64;
+    internal
+        .iter()
+        .zip(&degrees)
+        .map(|(&internal, &degree)| {
+            let share = degree as f64 / total;
+            internal as f64 / total - share * share
+        })
+        .sum()
+}
+
+/// Returns the vertices that have at least one neighbor in a different
+/// block, by increasing id.
+///
+/// `graph` is a [`GraphRef`], or a shared borrow of a [`Graph`](crate::Graph)
+/// or a [`GraphBuf`](crate::GraphBuf).
+///
+/// # Panics
+///
+/// This function panics if the length of `part` is different than the number
+/// of vertices.
+pub fn boundary_vertices<'g>(graph: impl Into<GraphRef<'g>>, part: &[Idx]) -> Vec<usize> {
+    let graph = graph.into();
+    assert_eq!(part.len(), graph.xadj.len() - 1);
+
+    (0..part.len())
+        .filter(|&v| csr::vertex_cut(graph.xadj, graph.adjncy, None, part, v) > 0)
+        .collect()
+}
+
+/// Returns the vertices of `block` that have at least one neighbor in a
+/// different block, by increasing id.
+///
+/// # Panics
+///
+/// This function panics if the length of `part` is different than the number
+/// of vertices.
+pub fn boundary_vertices_of_block<'g>(
+    graph: impl Into<GraphRef<'g>>,
+    part: &[Idx],
+    block: Idx,
+) -> Vec<usize> {
+    let mut boundary = boundary_vertices(graph, part);
+    boundary.retain(|&v| part[v] == block);
+    boundary
+}
+
+/// A summary of the quality of a partition, see [`report`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct PartitionReport {
+    /// The number of blocks.
+    pub n_parts: Idx,
+
+    /// The edge cut, see [`edge_cut`].
+    pub edge_cut: Idx,
+
+    /// The communication volume, see [`communication_volume`].
+    pub communication_volume: Idx,
+
+    /// The balance, see [`balance`].
+    pub balance: f64,
+
+    /// The number of vertices in each block.
+    pub block_sizes: Vec<usize>,
+}
+
+impl fmt::Display for PartitionReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "partition into {} blocks", self.n_parts)?;
+        writeln!(f, "  edge cut:             {}", self.edge_cut)?;
+        writeln!(f, "  communication volume: {}", self.communication_volume)?;
+        writeln!(f, "  balance:              {:.3}", self.balance)?;
+        write!(f, "  block sizes:         ")?;
+        for size in &self.block_sizes {
+            write!(f, " {size}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Computes the quality metrics of a partition of `graph` into `n_parts`
+/// blocks.
+///
+/// The balance accounts for the vertex weights of the graph, unless it has
+/// several weights per vertex.
+///
+/// # Panics
+///
+/// This function panics if:
+/// - the length of `part` is different than the number of vertices, or
+/// - an element of `part` is not in `0..n_parts`.
+pub fn report<'g>(graph: impl Into<GraphRef<'g>>, part: &[Idx], n_parts: Idx) -> PartitionReport {
+    let graph = graph.into();
+    let vwgt = graph.vwgt.filter(|_| graph.ncon == 1);
+    PartitionReport {
+        n_parts,
+        edge_cut: edge_cut(graph, part),
+        communication_volume: communication_volume(graph, part),
+        balance: balance(part, vwgt, n_parts),
+        block_sizes: block_sizes(part, n_parts),
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+
+    use crate::{
+        achieved_imbalance, balance, block_sizes, block_weights, boundary_vertices,
+        boundary_vertices_of_block, communication_volume, edge_cut, is_balanced, modularity,
+        per_block_cut, report, Graph, GraphBuf, GraphRef, Idx, Mode,
+    };
+
+    #[test]
+    fn balance_of_partition() {
+        let part = [0, 0, 1, 1, 0];
+        assert_eq!(balance(&part, None, 2), 1.2);
+        assert_eq!(balance(&part, Some(&[1, 1, 3, 2, 1]), 2), 1.25);
+        assert_eq!(balance(&part, Some(&[1, 1, 1, 2, 1]), 2), 1.0);
+
+        // The third block is empty.
+        assert_eq!(balance(&part, None, 3), 1.8);
+
+        assert_eq!(balance(&[], None, 2), 1.0);
+    }
+
+    #[test]
+    fn achieved_imbalance_of_partition() {
+        assert_eq!(achieved_imbalance(&[0, 1, 1, 0], None, 2), 0.0);
+        assert_eq!(
+            achieved_imbalance(&[0, 1, 1, 0], Some(&[2, 1, 0, 1]), 2),
+            0.5
+        );
+        let part = [0, 0, 1, 1, 0];
+        assert!((achieved_imbalance(&part, None, 2) - 0.2).abs() < 1e-12);
+    }
+
+    #[test]
+    fn balanced_partition() {
+        assert!(is_balanced(&[0, 1, 1, 0], None, 2, 0.03));
+        assert!(is_balanced(&[0, 1, 1, 0], None, 2, 0.0));
+        assert!(is_balanced(&[0, 0, 1, 1, 0], None, 2, 0.2));
+        assert!(!is_balanced(&[0, 0, 1, 1, 0], None, 2, 0.0));
+        assert!(!is_balanced(&[0, 1, 1, 0], Some(&[2, 1, 0, 1]), 2, 0.03));
+    }
+
+    #[test]
+    fn block_summaries() {
+        let part = [0, 0, 1, 1, 0];
+
+        let sizes = block_sizes(&part, 2);
+        assert_eq!(sizes, [3, 2]);
+        assert_eq!(sizes.iter().sum::<usize>(), part.len());
+        assert_eq!(block_weights(&part, None, 2), [3, 2]);
+        assert_eq!(block_weights(&part, Some(&[1, 2, 3, 4, 5]), 2), [8, 7]);
+
+        // The third block is empty.
+        assert_eq!(block_sizes(&part, 3), [3, 2, 0]);
+        assert_eq!(block_weights(&part, Some(&[1, 2, 3, 4, 5]), 3), [8, 7, 0]);
+    }
+
+    #[test]
+    fn communication_volume_of_partition() {
+        let mut xadj = vec![0, 2, 5, 7, 9, 12];
+        let mut adjncy = vec![1, 4, 0, 2, 4, 1, 3, 2, 4, 0, 1, 3];
+        let graph = Graph::new(&mut xadj, &mut adjncy);
+
+        // Vertices 1 to 4 each have a neighbor in the other block.
+        assert_eq!(communication_volume(&graph, &[0, 0, 1, 1, 0]), 4);
+        assert_eq!(communication_volume(&graph, &[0, 0, 0, 0, 0]), 0);
+        // Vertex 1 has neighbors in blocks 0 and 2, and vertex 4 in 1 and 2.
+        assert_eq!(communication_volume(&graph, &[0, 1, 2, 2, 0]), 7);
+    }
+
+    #[test]
+    fn edge_cut_of_partition() {
+        let mut xadj = vec![0, 2, 5, 7, 9, 12];
+        let mut adjncy = vec![1, 4, 0, 2, 4, 1, 3, 2, 4, 0, 1, 3];
+        let mut adjwgt = vec![1, 2, 1, 3, 4, 3, 5, 5, 6, 2, 4, 6];
+
+        let mut graph = Graph::new(&mut xadj, &mut adjncy);
+        assert_eq!(edge_cut(&graph, &[0, 0, 1, 1, 0]), 2);
+        assert_eq!(edge_cut(&graph, &[0, 0, 0, 0, 0]), 0);
+        let result = graph.partition(2, 0.03, true, 1234, Mode::Eco);
+        assert_eq!(edge_cut(&graph, &result.part), result.edge_cut);
+
+        let graph = graph.set_adjwgt(&mut adjwgt);
+        assert_eq!(edge_cut(&graph, &[0, 0, 1, 1, 0]), 9);
+    }
+
+    #[test]
+    fn boundary_of_partition() {
+        let xadj = [0, 2, 5, 7, 9, 12];
+        let adjncy = [1, 4, 0, 2, 4, 1, 3, 2, 4, 0, 1, 3];
+        let graph = GraphRef::new(&xadj, &adjncy);
+        let part = [0, 0, 1, 1, 0];
+
+        // Vertex 0 only has neighbors in its own block.
+        assert_eq!(boundary_vertices(graph, &part), [1, 2, 3, 4]);
+        assert_eq!(boundary_vertices_of_block(graph, &part, 0), [1, 4]);
+        assert_eq!(boundary_vertices_of_block(graph, &part, 1), [2, 3]);
+        assert_eq!(boundary_vertices(graph, &[0; 5]), []);
+    }
+
+    #[test]
+    fn modularity_of_communities() {
+        // Two triangles joined by the edge 2-3.
+        let graph =
+            GraphBuf::from_edge_list(6, &[(0, 1), (0, 2), (1, 2), (3, 4), (3, 5), (4, 5), (2, 3)]);
+        let part = [0, 0, 0, 1, 1, 1];
+        // Each triangle holds 3 of the 7 edges and half of the degrees.
+        let expected = 2.0 * (3.0 / 7.0 - 0.25);
+        assert!((modularity(&graph, &part, 2) - expected).abs() < 1e-12);
+        assert_eq!(modularity(&graph, &[0; 6], 1), 0.0);
+        assert!(modularity(&graph, &[0, 1, 0, 1, 0, 1], 2) < 0.0);
+
+        // A heavier bridge weakens the communities.
+        let weighted = GraphBuf::from_weighted_edge_list(
+            6,
+            &[(0, 1), (0, 2), (1, 2), (3, 4), (3, 5), (4, 5), (2, 3)],
+            &[1, 1, 1, 1, 1, 1, 7],
+        );
+        assert!(modularity(&weighted, &part, 2) < expected);
+
+        assert_eq!(modularity(GraphRef::new(&[0, 0], &[]), &[0], 1), 0.0);
+    }
+
+    #[test]
+    fn per_block_cut_of_partition() {
+        let xadj = [0, 2, 5, 7, 9, 12];
+        let adjncy = [1, 4, 0, 2, 4, 1, 3, 2, 4, 0, 1, 3];
+        let adjwgt = [1, 2, 1, 3, 4, 3, 5, 5, 6, 2, 4, 6];
+        let graph = GraphRef::new(&xadj, &adjncy);
+
+        let part = [0, 0, 1, 1, 2];
+        let cuts = per_block_cut(graph, &part, 3);
+        assert_eq!(cuts, [3, 2, 3]);
+        assert_eq!(cuts.iter().sum::<Idx>(), 2 * edge_cut(graph, &part));
+
+        let graph = graph.set_adjwgt(&adjwgt);
+        let part = [0, 0, 1, 1, 0];
+        assert_eq!(per_block_cut(graph, &part, 2), [9, 9]);
+        assert_eq!(edge_cut(graph, &part), 9);
+
+        // Empty blocks contribute nothing.
+        assert_eq!(per_block_cut(graph, &part, 3), [9, 9, 0]);
+    }
+
+    #[test]
+    fn metrics_of_shared_borrows() {
+        let xadj = [0, 2, 5, 7, 9, 12];
+        let adjncy = [1, 4, 0, 2, 4, 1, 3, 2, 4, 0, 1, 3];
+        let adjwgt = [1, 2, 1, 3, 4, 3, 5, 5, 6, 2, 4, 6];
+        let graph = GraphRef::new(&xadj, &adjncy).set_adjwgt(&adjwgt);
+        assert_eq!(edge_cut(graph, &[0, 0, 1, 1, 0]), 9);
+        assert_eq!(communication_volume(graph, &[0, 0, 1, 1, 0]), 4);
+
+        // No mutable borrow of the owned graph is needed.
+        let buf = GraphBuf::new(xadj.to_vec(), adjncy.to_vec());
+        let (first, second) = (&buf, &buf);
+        assert_eq!(edge_cut(first, &[0, 0, 1, 1, 1]), 3);
+        assert_eq!(report(second, &[0, 0, 1, 1, 1], 2).edge_cut, 3);
+    }
+
+    #[test]
+    #[should_panic]
+    fn balance_out_of_range() {
+        balance(&[0, 0, 2], None, 2);
+    }
+
+    #[test]
+    fn report_of_partition() {
+        let mut xadj = vec![0, 2, 5, 7, 9, 12];
+        let mut adjncy = vec![1, 4, 0, 2, 4, 1, 3, 2, 4, 0, 1, 3];
+        let graph = Graph::new(&mut xadj, &mut adjncy);
+
+        let report = report(&graph, &[0, 0, 1, 1, 0], 2);
+        assert_eq!(report.edge_cut, 2);
+        assert_eq!(report.communication_volume, 4);
+        assert_eq!(report.balance, 1.2);
+        assert_eq!(report.block_sizes, [3, 2]);
+        assert_eq!(
+            report.to_string(),
+            "partition into 2 blocks
+  edge cut:             2
+  communication volume: 4
+  balance:              1.200
+  block sizes:          3 2"
+        );
+    }
+}
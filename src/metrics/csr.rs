@@ -0,0 +1,182 @@
+//! The metrics that only need the CSR arrays of a graph.
+//!
+//! This module only depends on `core` and `alloc`, so that it can be used
+//! without `std` or KaHIP, by disabling the default `std` feature.
+
+use crate::Idx;
+use alloc::vec;
+use alloc::vec::Vec;
+
+/// Returns the balance of a partition, that is the weight of the heaviest
+/// block divided by the average weight of a block.
+///
+/// `part` holds the block of each vertex and `vwgt` the weight of each vertex,
+/// all vertices having a weight of 1 if it is `None`. Empty blocks count
+/// towards the average, so a perfectly balanced partition has a balance of 1
+/// and a partition with an allowed imbalance of 3% a balance of at most 1.03.
+///
+/// If the total weight is zero, the partition is considered balanced and 1 is
+/// returned.
+///
+/// # Panics
+///
+/// This function panics if:
+/// - `vwgt` and `part` have different lengths, or
+/// - an element of `part` is not in `0..n_parts`.
+pub fn balance(part: &[Idx], vwgt: Option<&[Idx]>, n_parts: Idx) -> f64 {
+    let weights = block_weights(part, vwgt, n_parts);
+    let total: i64 = weights.iter().sum();
+    if total == 0 {
+        return 1.0;
+    }
+    let max = *weights.iter().max().unwrap();
+    max as f64 * n_parts as f64 / total as f64
+}
+
+/// Returns the imbalance achieved by a partition, to be compared with the
+/// imbalance passed to KaHIP.
+///
+/// This is [`balance`] minus one, that is
+/// `max_block_weight * n_parts / total_weight - 1`, with the same conventions.
+/// KaHIP allows each block to weigh at most `(1 + imbalance)` times the
+/// average, so a partition within the requested imbalance has an achieved
+/// imbalance of at most the requested one, e.g. 0.03 for 3%, up to the
+/// rounding of the average weight to an integer. A perfectly balanced
+/// partition has an achieved imbalance of zero.
+///
+/// # Panics
+///
+/// See [`balance`].
+pub fn achieved_imbalance(part: &[Idx], vwgt: Option<&[Idx]>, n_parts: Idx) -> f64 {
+    balance(part, vwgt, n_parts) - 1.0
+}
+
+/// Returns whether the achieved imbalance of a partition is at most
+/// `tolerance`, e.g. `0.03` for 3%, see [`achieved_imbalance`].
+///
+/// The comparison is exact: when the total weight is not a multiple of
+/// `n_parts`, even the best partition has a positive achieved imbalance, and
+/// may be rejected by a tolerance that KaHIP's rounded bound would accept.
+///
+/// # Panics
+///
+/// See [`balance`].
+pub fn is_balanced(part: &[Idx], vwgt: Option<&[Idx]>, n_parts: Idx, tolerance: f64) -> bool {
+    achieved_imbalance(part, vwgt, n_parts) <= tolerance
+}
+
+/// Returns the number of vertices in each block.
+///
+/// # Panics
+///
+/// This function panics if an element of `part` is not in `0..n_parts`.
+pub fn block_sizes(part: &[Idx], n_parts: Idx) -> Vec<usize> {
+    let mut sizes = vec![0; n_parts as usize];
+    for &p in part {
+        sizes[p as usize] += 1;
+    }
+    sizes
+}
+
+/// Returns the total vertex weight of each block.
+///
+/// `vwgt` holds the weight of each vertex, all vertices having a weight of 1
+/// if it is `None`. Weights are summed as 64-bit integers to avoid overflows.
+///
+/// # Panics
+///
+/// This function panics if:
+/// - `vwgt` and `part` have different lengths, or
+/// - an element of `part` is not in `0..n_parts`.
+pub fn block_weights(part: &[Idx], vwgt: Option<&[Idx]>, n_parts: Idx) -> Vec<i64> {
+    let Some(vwgt) = vwgt else {
+        return block_sizes(part, n_parts)
+            .into_iter()
+            .map(|size| size as i64)
+            .collect();
+    };
+    assert_eq!(vwgt.len(), part.len());
+    let mut weights = vec![0; n_parts as usize];
+    for (&p, &w) in part.iter().zip(vwgt) {
+        weights[p as usize] += w as i64;
+    }
+    weights
+}
+
+/// Returns the total communication volume of a partition.
+///
+/// Each vertex is counted once for every block, other than its own, that
+/// holds at least one of its neighbors. This is the total volume over all
+/// blocks, i.e. the amount of data sent when every vertex sends its value to
+/// each remote block that needs it. Vertex and edge weights are ignored.
+///
+/// Unlike the edge cut, a vertex with several neighbors in the same remote
+/// block only counts once.
+///
+/// # Panics
+///
+/// This function panics if the length of `part` is different than the number
+/// of vertices, i.e. `xadj.len() - 1`.
+pub fn communication_volume(xadj: &[Idx], adjncy: &[Idx], part: &[Idx]) -> Idx {
+    assert_eq!(part.len(), xadj.len() - 1);
+
+    let mut remote = Vec::new();
+    (0..part.len())
+        .map(|v| vertex_volume(xadj, adjncy, part, v, &mut remote))
+        .sum()
+}
+
+/// Returns the number of blocks other than its own that hold a neighbor of
+/// `v`, using `remote` as scratch space.
+pub(crate) fn vertex_volume(
+    xadj: &[Idx],
+    adjncy: &[Idx],
+    part: &[Idx],
+    v: usize,
+    remote: &mut Vec<Idx>,
+) -> Idx {
+    remote.clear();
+    for &u in &adjncy[xadj[v] as usize..xadj[v + 1] as usize] {
+        let block = part[u as usize];
+        if block != part[v] && !remote.contains(&block) {
+            remote.push(block);
+        }
+    }
+    remote.len() as Idx
+}
+
+/// Returns the edge cut of a partition, that is the total weight of the edges
+/// between different blocks.
+///
+/// Each edge counts once, although it is stored in both directions. Edges
+/// have a weight of 1 if `adjwgt` is `None`.
+///
+/// # Panics
+///
+/// This function panics if the length of `part` is different than the number
+/// of vertices, i.e. `xadj.len() - 1`.
+pub fn edge_cut(xadj: &[Idx], adjncy: &[Idx], adjwgt: Option<&[Idx]>, part: &[Idx]) -> Idx {
+    assert_eq!(part.len(), xadj.len() - 1);
+
+    let cut: Idx = (0..part.len())
+        .map(|v| vertex_cut(xadj, adjncy, adjwgt, part, v))
+        .sum();
+    cut / 2
+}
+
+/// Returns the total weight of the edges from `v` to other blocks.
+pub(crate) fn vertex_cut(
+    xadj: &[Idx],
+    adjncy: &[Idx],
+    adjwgt: Option<&[Idx]>,
+    part: &[Idx],
+    v: usize,
+) -> Idx {
+    let mut cut = 0;
+    for k in xadj[v] as usize..xadj[v + 1] as usize {
+        if part[v] != part[adjncy[k] as usize] {
+            cut += adjwgt.map_or(1, |adjwgt| adjwgt[k]);
+        }
+    }
+    cut
+}
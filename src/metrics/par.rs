@@ -0,0 +1,71 @@
+//! Parallel versions of the metrics, computed with rayon.
+
+use super::csr::{vertex_cut, vertex_volume};
+use crate::{GraphRef, Idx};
+use rayon::prelude::*;
+
+/// Same as [`communication_volume`](crate::communication_volume), with the
+/// vertices split across rayon's threads.
+///
+/// The result is the same as with the serial version.
+///
+/// # Panics
+///
+/// This function panics if the length of `part` is different than the number
+/// of vertices.
+pub fn par_communication_volume<'g>(graph: impl Into<GraphRef<'g>>, part: &[Idx]) -> Idx {
+    let graph = graph.into();
+    assert_eq!(part.len(), graph.xadj.len() - 1);
+
+    (0..part.len())
+        .into_par_iter()
+        .map_init(Vec::new, |remote, v| {
+            vertex_volume(graph.xadj, graph.adjncy, part, v, remote)
+        })
+        .sum()
+}
+
+/// Same as [`edge_cut`](crate::edge_cut), with the vertices split across
+/// rayon's threads.
+///
+/// The result is the same as with the serial version.
+///
+/// # Panics
+///
+/// This function panics if the length of `part` is different than the number
+/// of vertices.
+pub fn par_edge_cut<'g>(graph: impl Into<GraphRef<'g>>, part: &[Idx]) -> Idx {
+    let graph = graph.into();
+    assert_eq!(part.len(), graph.xadj.len() - 1);
+
+    let cut: Idx = (0..part.len())
+        .into_par_iter()
+        .map(|v| vertex_cut(graph.xadj, graph.adjncy, graph.adjwgt, part, v))
+        .sum();
+    cut / 2
+}
+
+#[cfg(test)]
+mod tests {
+
+    use crate::testgraphs::grid_2d;
+    use crate::{communication_volume, edge_cut, par_communication_volume, par_edge_cut, Idx};
+
+    #[test]
+    fn same_as_serial() {
+        let graph = grid_2d(60, 60);
+        let n_edges = graph.adjncy().len();
+        let weighted = graph
+            .clone()
+            .set_adjwgt((0..n_edges).map(|k| (k % 7) as Idx + 1).collect());
+        // Scattered blocks, so that vertices have neighbors in several.
+        let part: Vec<Idx> = (0..3600).map(|v| (v * 7 % 13 % 5) as Idx).collect();
+
+        assert_eq!(par_edge_cut(&graph, &part), edge_cut(&graph, &part));
+        assert_eq!(par_edge_cut(&weighted, &part), edge_cut(&weighted, &part));
+        assert_eq!(
+            par_communication_volume(&graph, &part),
+            communication_volume(&graph, &part)
+        );
+    }
+}
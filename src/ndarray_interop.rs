@@ -0,0 +1,80 @@
+//! Conversions from [`ndarray`]'s dense matrices.
+
+use crate::{GraphBuf, GraphError, Idx};
+use ndarray::ArrayView2;
+
+impl GraphBuf {
+    /// Creates a new [`GraphBuf`] from a dense adjacency matrix.
+    ///
+    /// Vertices `u` and `v` are neighbors when the entry at row `u` and column
+    /// `v` is nonzero, and the entry is the weight of the edge. Diagonal
+    /// entries are ignored since they would be self-loops.
+    ///
+    /// Returns an error if the matrix is not square or not symmetric.
+    pub fn from_dense(adj: ArrayView2<Idx>) -> Result<GraphBuf, GraphError> {
+        let (rows, cols) = adj.dim();
+        if rows != cols {
+            return Err(GraphError::NotSquare { rows, cols });
+        }
+        for ((row, col), &weight) in adj.indexed_iter() {
+            if row < col && weight != adj[(col, row)] {
+                return Err(GraphError::NotSymmetric { row, col });
+            }
+        }
+
+        let mut xadj = Vec::with_capacity(rows + 1);
+        let mut adjncy = Vec::new();
+        let mut adjwgt = Vec::new();
+
+        xadj.push(0);
+        for (u, row) in adj.outer_iter().enumerate() {
+            for (v, &weight) in row.iter().enumerate() {
+                if v == u || weight == 0 {
+                    continue;
+                }
+                adjncy.push(v as Idx);
+                adjwgt.push(weight);
+            }
+            xadj.push(adjncy.len() as Idx);
+        }
+
+        Ok(GraphBuf::new(xadj, adjncy).set_adjwgt(adjwgt))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use crate::{GraphBuf, GraphError};
+    use ndarray::{arr2, Array2};
+
+    #[test]
+    fn from_dense() {
+        let adj = arr2(&[
+            [9, 1, 0, 2], //
+            [1, 9, 3, 0],
+            [0, 3, 9, 4],
+            [2, 0, 4, 9],
+        ]);
+        let graph = GraphBuf::from_dense(adj.view()).unwrap();
+
+        assert_eq!(graph.xadj(), [0, 2, 4, 6, 8]);
+        assert_eq!(graph.adjncy(), [1, 3, 0, 2, 1, 3, 0, 2]);
+        assert_eq!(graph.adjwgt(), Some(&[1, 2, 1, 3, 3, 4, 2, 4][..]));
+    }
+
+    #[test]
+    fn from_dense_invalid() {
+        let adj = Array2::zeros((2, 3));
+        assert_eq!(
+            GraphBuf::from_dense(adj.view()),
+            Err(GraphError::NotSquare { rows: 2, cols: 3 })
+        );
+
+        let adj = arr2(&[[0, 1, 0], [1, 0, 2], [0, 3, 0]]);
+        assert_eq!(
+            GraphBuf::from_dense(adj.view()),
+            Err(GraphError::NotSymmetric { row: 1, col: 2 })
+        );
+    }
+}
@@ -0,0 +1,48 @@
+//! Partitions of many graphs at once, with rayon.
+
+use crate::{GraphBuf, PartitionConfig, PartitionResult};
+use rayon::prelude::*;
+
+/// Partitions each of `graphs` with `cfg`, spreading the graphs across
+/// rayon's threads. Returns the result of each graph, in the same order.
+///
+/// KaHIP is not reentrant, so its calls are serialized (see the thread
+/// safety notes of [`Graph`](crate::Graph)): the work around them, such as
+/// the checks of the arguments, graphs without edges or with a single
+/// block, and the placement of fixed vertices, runs in parallel, while the
+/// partitions computed by KaHIP run one at a time. The results are the same
+/// as partitioning the graphs one after another.
+///
+/// # Panics
+///
+/// This function panics if the arguments are out of range for one of the
+/// graphs, see [`Graph::try_partition_with`](crate::Graph::try_partition_with).
+pub fn partition_many(graphs: &mut [GraphBuf], cfg: &PartitionConfig) -> Vec<PartitionResult> {
+    graphs
+        .par_iter_mut()
+        .map(|graph| graph.as_graph().partition_with(cfg))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+
+    use crate::testgraphs::grid_2d;
+    use crate::{edge_cut, partition_many, GraphBuf, PartitionConfig};
+
+    #[test]
+    fn same_as_serial() {
+        let mut graphs: Vec<GraphBuf> = (2..10).map(|n| grid_2d(n, n + 1)).collect();
+        let cfg = PartitionConfig::new(3).set_seed(1234);
+
+        let results = partition_many(&mut graphs, &cfg);
+        assert_eq!(results.len(), graphs.len());
+        for (graph, result) in graphs.iter_mut().zip(&results) {
+            let n = graph.xadj().len() - 1;
+            assert_eq!(result.part.len(), n);
+            assert!(result.part.iter().all(|&block| (0..3).contains(&block)));
+            assert_eq!(result.edge_cut, edge_cut(&*graph, &result.part));
+            assert_eq!(*result, graph.as_graph().partition_with(&cfg));
+        }
+    }
+}
@@ -0,0 +1,152 @@
+//! Distributed partitioning with ParHIP, KaHIP's MPI-parallel partitioner.
+
+use crate::Idx;
+use kahip_sys::parhip as p;
+use mpi::ffi::MPI_Comm;
+use mpi::raw::AsRaw;
+
+/// The integer type used by ParHIP for vertex ids and weights.
+///
+/// This is a C `unsigned long long`, whatever the `idx64` feature.
+pub type ParIdx = u64;
+
+/// The preconfigurations of ParHIP, as numbered in `parhip_interface.h`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParMode {
+    UltraFastMesh = 0,
+    FastMesh = 1,
+    EcoMesh = 2,
+    UltraFastSocial = 3,
+    FastSocial = 4,
+    EcoSocial = 5,
+}
+
+/// The part of a distributed graph held by one MPI process.
+///
+/// The vertices are numbered globally and distributed in contiguous ranges:
+/// `vtxdist` has one more element than there are processes, and the process
+/// of rank `r` holds vertices `vtxdist[r]` to `vtxdist[r + 1]` (excluded).
+/// `vtxdist` must be the same on every process.
+///
+/// `xadj` and `adjncy` are the CSR adjacency of the local vertices only:
+/// `xadj` has one more element than there are local vertices, and the
+/// neighbors of the `i`-th local vertex are the global ids
+/// `adjncy[xadj[i]..xadj[i + 1]]`, which may be held by other processes.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DistGraph<'a> {
+    vtxdist: &'a [ParIdx],
+    xadj: &'a [ParIdx],
+    adjncy: &'a [ParIdx],
+    vwgt: Option<&'a [ParIdx]>,
+    adjwgt: Option<&'a [ParIdx]>,
+}
+
+impl<'a> DistGraph<'a> {
+    /// Creates a new [`DistGraph`] object to be partitioned.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if `vtxdist` has less than two elements, if
+    /// `xadj` is empty, or if the last element of `xadj` is not the length of
+    /// `adjncy`.
+    pub fn new(vtxdist: &'a [ParIdx], xadj: &'a [ParIdx], adjncy: &'a [ParIdx]) -> DistGraph<'a> {
+        assert!(
+            vtxdist.len() >= 2,
+            "vtxdist must have at least two elements"
+        );
+        assert_eq!(xadj.last().map(|&l| l as usize), Some(adjncy.len()));
+
+        DistGraph {
+            vtxdist,
+            xadj,
+            adjncy,
+            vwgt: None,
+            adjwgt: None,
+        }
+    }
+
+    /// Sets the weights of the local vertices.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if `vwgt` has not one weight per local vertex.
+    pub fn set_vwgt(mut self, vwgt: &'a [ParIdx]) -> DistGraph<'a> {
+        assert_eq!(vwgt.len(), self.xadj.len() - 1);
+        self.vwgt = Some(vwgt);
+        self
+    }
+
+    /// Sets the weights of the local edges, in the same order as `adjncy`.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if `adjwgt` and `adjncy` have different lengths.
+    pub fn set_adjwgt(mut self, adjwgt: &'a [ParIdx]) -> DistGraph<'a> {
+        assert_eq!(adjwgt.len(), self.adjncy.len());
+        self.adjwgt = Some(adjwgt);
+        self
+    }
+
+    /// Partitions the distributed graph into `n_parts` blocks.
+    ///
+    /// This is a collective call: every process of `comm` must make it with
+    /// its own part of the graph. Returns the block of each local vertex, and
+    /// the edge cut of the whole partition.
+    pub fn partition<C>(
+        &self,
+        comm: &C,
+        n_parts: Idx,
+        imbalance: f64,
+        suppress_output: bool,
+        seed: Idx,
+        mode: ParMode,
+    ) -> (Vec<ParIdx>, Idx)
+    where
+        C: AsRaw<Raw = MPI_Comm>,
+    {
+        let n_vertices = self.xadj.len() - 1;
+        // ParHIP expects weights, unit ones stand for unweighted graphs.
+        let unit_vwgt;
+        let vwgt = match self.vwgt {
+            Some(vwgt) => vwgt,
+            None => {
+                unit_vwgt = vec![1; n_vertices];
+                &unit_vwgt
+            }
+        };
+        let unit_adjwgt;
+        let adjwgt = match self.adjwgt {
+            Some(adjwgt) => adjwgt,
+            None => {
+                unit_adjwgt = vec![1; self.adjncy.len()];
+                &unit_adjwgt
+            }
+        };
+
+        let mut part = vec![0; n_vertices];
+        let mut n_parts = n_parts as _;
+        let mut imbalance = imbalance;
+        let mut edge_cut = 0;
+        let mut comm = comm.as_raw();
+
+        // The graph is only read by ParHIP.
+        unsafe {
+            p::ParHIPPartitionKWay(
+                self.vtxdist.as_ptr() as *mut _,
+                self.xadj.as_ptr() as *mut _,
+                self.adjncy.as_ptr() as *mut _,
+                vwgt.as_ptr() as *mut _,
+                adjwgt.as_ptr() as *mut _,
+                &mut n_parts,
+                &mut imbalance,
+                suppress_output,
+                seed as _,
+                mode as _,
+                &mut edge_cut,
+                part.as_mut_ptr(),
+                &mut comm as *mut MPI_Comm as *mut p::MPI_Comm,
+            );
+        }
+        (part, edge_cut as Idx)
+    }
+}
@@ -0,0 +1,330 @@
+//! A partition of the vertices of a graph.
+
+use crate::{Idx, PartitionError};
+use std::cmp::Reverse;
+use std::collections::HashMap;
+use std::iter::{Copied, Enumerate};
+use std::{slice, vec};
+
+/// The block of each vertex of a graph, split into a known number of blocks.
+///
+/// With the `serde` feature, deserialized partitions are checked the same way
+/// as [`Partition::try_new`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "RawPartition"))]
+pub struct Partition {
+    part: Vec<Idx>,
+    n_parts: Idx,
+}
+
+/// A [`Partition`] as deserialized, before its blocks are checked.
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+struct RawPartition {
+    part: Vec<Idx>,
+    n_parts: Idx,
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<RawPartition> for Partition {
+    type Error = PartitionError;
+
+    fn try_from(raw: RawPartition) -> Result<Partition, PartitionError> {
+        Partition::try_new(raw.part, raw.n_parts)
+    }
+}
+
+impl Partition {
+    /// Creates a new [`Partition`] of `part.len()` vertices into `n_parts`
+    /// blocks, where vertex `v` is in block `part[v]`.
+    ///
+    /// Some blocks may be empty.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if an element of `part` is not in `0..n_parts`.
+    pub fn new(part: Vec<Idx>, n_parts: Idx) -> Partition {
+        Partition::try_new(part, n_parts).unwrap()
+    }
+
+    /// Same as [`Partition::new`], returning an error if a vertex is not in
+    /// `0..n_parts`.
+    pub fn try_new(part: Vec<Idx>, n_parts: Idx) -> Result<Partition, PartitionError> {
+        if let Some((vertex, &block)) = part
+            .iter()
+            .enumerate()
+            .find(|(_, p)| !(0..n_parts).contains(p))
+        {
+            return Err(PartitionError::BlockOutOfRange { vertex, block });
+        }
+        Ok(Partition { part, n_parts })
+    }
+
+    /// The block of `vertex`.
+    pub fn block_of(&self, vertex: usize) -> Idx {
+        self.part[vertex]
+    }
+
+    /// The number of blocks.
+    pub fn blocks(&self) -> usize {
+        self.n_parts as usize
+    }
+
+    /// The number of vertices.
+    pub fn len(&self) -> usize {
+        self.part.len()
+    }
+
+    /// Returns whether there is no vertex.
+    pub fn is_empty(&self) -> bool {
+        self.part.is_empty()
+    }
+
+    /// Iterates over the vertices of `block`, in increasing order.
+    pub fn members(&self, block: Idx) -> impl Iterator<Item = usize> + '_ {
+        self.part
+            .iter()
+            .enumerate()
+            .filter(move |&(_, &p)| p == block)
+            .map(|(v, _)| v)
+    }
+
+    /// Returns the vertices of each block, in increasing order.
+    pub fn into_groups(self) -> Vec<Vec<usize>> {
+        let mut groups = vec![Vec::new(); self.blocks()];
+        for (v, &p) in self.part.iter().enumerate() {
+            groups[p as usize].push(v);
+        }
+        groups
+    }
+
+    /// The block of each vertex.
+    pub fn as_slice(&self) -> &[Idx] {
+        &self.part
+    }
+
+    /// Returns the block of each vertex.
+    pub fn into_vec(self) -> Vec<Idx> {
+        self.part
+    }
+
+    /// Iterates over the vertices and their block, as `(vertex, block)`
+    /// pairs in increasing order of vertex.
+    pub fn iter(&self) -> Enumerate<Copied<slice::Iter<'_, Idx>>> {
+        self.part.iter().copied().enumerate()
+    }
+}
+
+impl IntoIterator for Partition {
+    type Item = (usize, Idx);
+    type IntoIter = Enumerate<vec::IntoIter<Idx>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.part.into_iter().enumerate()
+    }
+}
+
+impl<'a> IntoIterator for &'a Partition {
+    type Item = (usize, Idx);
+    type IntoIter = Enumerate<Copied<slice::Iter<'a, Idx>>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// Moves the vertices of `part` to the blocks they are fixed to.
+///
+/// `fixed` holds the block of each vertex, or -1 for free vertices. Blocks are
+/// first greedily renamed so that most fixed vertices are already in their
+/// block, then the remaining ones are moved.
+pub(crate) fn pin_fixed_vertices(part: &mut [Idx], fixed: &[Idx], n_parts: Idx) {
+    let mut votes = HashMap::new();
+    for (&p, &f) in part.iter().zip(fixed) {
+        if f >= 0 {
+            *votes.entry((p, f)).or_insert(0) += 1;
+        }
+    }
+    let mut votes: Vec<_> = votes.into_iter().collect();
+    votes.sort_unstable_by_key(|&((p, f), count)| (Reverse(count), p, f));
+
+    let mut label = vec![None; n_parts as usize];
+    let mut used = vec![false; n_parts as usize];
+    for ((p, f), _) in votes {
+        if label[p as usize].is_none() && !used[f as usize] {
+            label[p as usize] = Some(f);
+            used[f as usize] = true;
+        }
+    }
+    let mut unused = (0..n_parts).filter(|&f| !used[f as usize]);
+    for l in &mut label {
+        if l.is_none() {
+            *l = unused.next();
+        }
+    }
+
+    for (p, &f) in part.iter_mut().zip(fixed) {
+        *p = if f >= 0 {
+            f
+        } else {
+            label[*p as usize].unwrap()
+        };
+    }
+}
+
+/// Assigns the vertices of a graph without edges to `n_parts` blocks.
+///
+/// Any assignment has a zero edge cut, so only the balance matters: fixed
+/// vertices go to their block, and free ones are added in turn to the block
+/// that is the least loaded relatively to its target weight. Vertices of
+/// equal weight thus end up distributed round-robin.
+pub(crate) fn spread_isolated(
+    part: &mut [Idx],
+    vwgt: Option<&[Idx]>,
+    fixed: Option<&[Idx]>,
+    targets: Option<&[f64]>,
+    n_parts: Idx,
+) {
+    let weight = |v: usize| vwgt.map_or(1.0, |vwgt| vwgt[v] as f64);
+    let target = |b: usize| targets.map_or(1.0, |targets| targets[b]);
+
+    let mut load = vec![0.0; n_parts as usize];
+    if let Some(fixed) = fixed {
+        for (v, &f) in fixed.iter().enumerate() {
+            if f >= 0 {
+                part[v] = f;
+                load[f as usize] += weight(v);
+            }
+        }
+    }
+    for v in 0..part.len() {
+        if fixed.is_some_and(|fixed| fixed[v] >= 0) {
+            continue;
+        }
+        let w = weight(v);
+        let block = (0..load.len())
+            .min_by(|&a, &b| {
+                let a = (load[a] + w) / target(a);
+                let b = (load[b] + w) / target(b);
+                a.total_cmp(&b)
+            })
+            .unwrap();
+        part[v] = block as Idx;
+        load[block] += w;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::{pin_fixed_vertices, spread_isolated};
+    use crate::{Idx, Partition, PartitionError};
+
+    #[test]
+    fn members_and_groups() {
+        let partition = Partition::new(vec![0, 2, 1, 1, 0, 2, 0], 4);
+
+        assert_eq!(partition.blocks(), 4);
+        assert_eq!(partition.len(), 7);
+        for block in 0..4 {
+            let members: Vec<_> = partition.members(block).collect();
+            let expected: Vec<_> = (0..7).filter(|&v| partition.block_of(v) == block).collect();
+            assert_eq!(members, expected);
+        }
+
+        let groups = partition.clone().into_groups();
+        assert_eq!(groups, [vec![0, 4, 6], vec![2, 3], vec![1, 5], vec![]]);
+        for (block, group) in groups.iter().enumerate() {
+            assert!(group.iter().all(|&v| partition.block_of(v) == block as Idx));
+        }
+    }
+
+    #[test]
+    fn into_iter() {
+        let part = vec![0, 2, 1, 1, 0, 2, 0];
+        let partition = Partition::new(part.clone(), 4);
+
+        let mut blocks = vec![-1; partition.len()];
+        for (v, block) in &partition {
+            blocks[v] = block;
+        }
+        assert_eq!(blocks, part);
+        assert!(partition.iter().eq(part.iter().copied().enumerate()));
+
+        let pairs: Vec<(usize, Idx)> = partition.into_iter().collect();
+        let blocks: Vec<Idx> = pairs.iter().map(|&(_, block)| block).collect();
+        assert_eq!(blocks, part);
+        assert!(pairs.iter().enumerate().all(|(i, &(v, _))| i == v));
+    }
+
+    #[test]
+    fn pin_fixed() {
+        // Blocks 0 and 1 are swapped, block 2 is kept.
+        let mut part = vec![0, 0, 1, 1, 2, 2];
+        pin_fixed_vertices(&mut part, &[1, -1, -1, 0, -1, -1], 3);
+        assert_eq!(part, [1, 1, 0, 0, 2, 2]);
+
+        // Vertex 1 can only be moved.
+        let mut part = vec![0, 0, 1, 1];
+        pin_fixed_vertices(&mut part, &[0, 1, -1, -1], 2);
+        assert_eq!(part, [0, 1, 1, 1]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn out_of_range() {
+        Partition::new(vec![0, 1, 2], 2);
+    }
+
+    #[test]
+    fn try_new() {
+        assert!(Partition::try_new(vec![0, 1, 1], 2).is_ok());
+        assert_eq!(
+            Partition::try_new(vec![0, 1, 2], 2),
+            Err(PartitionError::BlockOutOfRange {
+                vertex: 2,
+                block: 2
+            })
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_round_trip() {
+        let partition = Partition::new(vec![0, 2, 1, 1, 0, 2, 0], 4);
+        let json = serde_json::to_string(&partition).unwrap();
+        assert_eq!(serde_json::from_str::<Partition>(&json).unwrap(), partition);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_rejects_out_of_range() {
+        let json = r#"{"part":[0,2,1],"n_parts":2}"#;
+        assert!(serde_json::from_str::<Partition>(json).is_err());
+        let json = r#"{"part":[0,-1],"n_parts":2}"#;
+        assert!(serde_json::from_str::<Partition>(json).is_err());
+    }
+
+    #[test]
+    fn spread_isolated_vertices() {
+        let mut part = vec![-1; 7];
+        spread_isolated(&mut part, None, None, None, 3);
+        assert_eq!(part, [0, 1, 2, 0, 1, 2, 0]);
+
+        let vwgt = [4, 1, 1, 1, 1];
+        let mut part = vec![-1; 5];
+        spread_isolated(&mut part, Some(&vwgt), None, None, 2);
+        assert_eq!(part, [0, 1, 1, 1, 1]);
+
+        let fixed = [-1, 0, 0, -1];
+        let mut part = vec![-1; 4];
+        spread_isolated(&mut part, None, Some(&fixed), None, 2);
+        assert_eq!(part, [1, 0, 0, 1]);
+
+        let targets = [0.75, 0.25];
+        let mut part = vec![-1; 4];
+        spread_isolated(&mut part, None, None, Some(&targets), 2);
+        assert_eq!(part, [0, 0, 0, 1]);
+    }
+}
@@ -0,0 +1,119 @@
+//! Repeated partitions with a reused output buffer.
+
+use crate::{Graph, Idx, PartitionConfig, PartitionError};
+
+/// Partitions graphs one after another into the same buffer.
+///
+/// [`Graph::partition_with`] allocates the block vector of every call. A
+/// [`Partitioner`] instead keeps it between calls, and only grows it when a
+/// graph has more vertices than the previous ones, which saves allocations
+/// when sweeping over many graphs of similar sizes.
+///
+/// ```no_run
+/// # use kahip::{GraphBuf, PartitionConfig, Partitioner};
+/// # let graphs: Vec<GraphBuf> = Vec::new();
+/// let mut partitioner = Partitioner::new(PartitionConfig::new(4));
+/// for mut graph in graphs {
+///     let part = partitioner.run(&mut graph.as_graph());
+///     println!("{part:?}");
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct Partitioner {
+    cfg: PartitionConfig,
+    part: Vec<Idx>,
+    edge_cut: Idx,
+}
+
+impl Partitioner {
+    /// Creates a new [`Partitioner`] running `cfg`.
+    pub fn new(cfg: PartitionConfig) -> Partitioner {
+        Partitioner {
+            cfg,
+            part: Vec::new(),
+            edge_cut: 0,
+        }
+    }
+
+    /// Reserves room in the buffer for graphs of up to `nvtxs` vertices, so
+    /// that partitioning them does not allocate, not even the first time.
+    ///
+    /// Without it, the buffer grows on the first call and whenever a graph
+    /// is larger than all the previous ones. It never shrinks.
+    pub fn with_capacity(mut self, nvtxs: usize) -> Partitioner {
+        self.part.reserve(nvtxs);
+        self
+    }
+
+    /// Partitions `graph`, returning the block of each vertex.
+    ///
+    /// The result borrows the buffer of the partitioner, and is overwritten
+    /// by the next call.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if the arguments are out of range, see
+    /// [`Graph::try_partition_with`].
+    pub fn run(&mut self, graph: &mut Graph) -> &[Idx] {
+        self.try_run(graph).unwrap()
+    }
+
+    /// Same as [`Partitioner::run`], returning an error if the arguments are
+    /// out of range.
+    pub fn try_run(&mut self, graph: &mut Graph) -> Result<&[Idx], PartitionError> {
+        self.part.clear();
+        self.part.resize(graph.num_vertices(), 0);
+        self.edge_cut = graph.try_partition_into(&mut self.part, &self.cfg)?;
+        Ok(&self.part)
+    }
+
+    /// The edge cut of the last partition.
+    pub fn edge_cut(&self) -> Idx {
+        self.edge_cut
+    }
+
+    /// The configuration of the partitions.
+    pub fn config(&self) -> &PartitionConfig {
+        &self.cfg
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use crate::testgraphs::grid_2d;
+    use crate::{edge_cut, PartitionConfig, Partitioner};
+
+    #[test]
+    fn reuses_buffer() {
+        let mut partitioner = Partitioner::new(PartitionConfig::new(4).set_seed(1234));
+
+        let mut large = grid_2d(20, 20);
+        let part = partitioner.run(&mut large.as_graph()).to_vec();
+        assert_eq!(part.len(), 400);
+        assert_eq!(partitioner.edge_cut(), edge_cut(&large, &part));
+        let capacity = partitioner.part.capacity();
+        let buffer = partitioner.part.as_ptr();
+
+        for (nx, ny) in [(10, 10), (20, 20), (15, 20)] {
+            let mut graph = grid_2d(nx, ny);
+            let part = partitioner.run(&mut graph.as_graph());
+            assert_eq!(part.len(), nx * ny);
+            assert_eq!(partitioner.part.capacity(), capacity);
+            assert_eq!(partitioner.part.as_ptr(), buffer);
+        }
+    }
+
+    #[test]
+    fn with_capacity() {
+        let mut partitioner = Partitioner::new(PartitionConfig::new(4)).with_capacity(100);
+        let capacity = partitioner.part.capacity();
+        assert!(capacity >= 100);
+        let buffer = partitioner.part.as_ptr();
+
+        let mut graph = grid_2d(10, 10);
+        assert_eq!(partitioner.run(&mut graph.as_graph()).len(), 100);
+        assert_eq!(partitioner.part.capacity(), capacity);
+        assert_eq!(partitioner.part.as_ptr(), buffer);
+    }
+}
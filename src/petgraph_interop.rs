@@ -0,0 +1,133 @@
+//! Conversions from [`petgraph`]'s undirected graphs.
+
+use crate::{GraphBuf, Idx};
+use petgraph::graph::{IndexType, NodeIndex};
+use petgraph::stable_graph::StableGraph;
+use petgraph::visit::{EdgeRef, IntoEdgeReferences, NodeIndexable};
+use petgraph::{Graph, Undirected};
+
+impl GraphBuf {
+    /// Creates a new [`GraphBuf`] from a [`petgraph::Graph`].
+    ///
+    /// Vertex `v` is the node of index `v`. As with
+    /// [`GraphBuf::from_edge_list`], parallel edges are merged and self-loops
+    /// are dropped. Node and edge weights are ignored.
+    pub fn from_petgraph<N, E, Ix: IndexType>(g: &Graph<N, E, Undirected, Ix>) -> GraphBuf {
+        let mut graph = GraphBuf::from_petgraph_weighted(g, |_| 1);
+        graph.adjwgt = None;
+        graph
+    }
+
+    /// Same as [`GraphBuf::from_petgraph`], computing the weight of each edge
+    /// with `weight`.
+    ///
+    /// The weights of parallel edges are summed.
+    pub fn from_petgraph_weighted<N, E, Ix, F>(
+        g: &Graph<N, E, Undirected, Ix>,
+        mut weight: F,
+    ) -> GraphBuf
+    where
+        Ix: IndexType,
+        F: FnMut(&E) -> Idx,
+    {
+        let edges = g.edge_references().map(|e| {
+            (
+                e.source().index() as Idx,
+                e.target().index() as Idx,
+                weight(e.weight()),
+            )
+        });
+        GraphBuf::from_weighted_pairs(g.node_count(), edges)
+    }
+
+    /// Creates a new [`GraphBuf`] from a [`StableGraph`].
+    ///
+    /// The indices of a [`StableGraph`] may have holes left by removed nodes,
+    /// so nodes are renumbered. Also returns the node of each vertex, e.g. to
+    /// map a partition back onto the graph.
+    pub fn from_stable_petgraph<N, E, Ix: IndexType>(
+        g: &StableGraph<N, E, Undirected, Ix>,
+    ) -> (GraphBuf, Vec<NodeIndex<Ix>>) {
+        let (mut graph, nodes) = GraphBuf::from_stable_petgraph_weighted(g, |_| 1);
+        graph.adjwgt = None;
+        (graph, nodes)
+    }
+
+    /// Same as [`GraphBuf::from_stable_petgraph`], computing the weight of
+    /// each edge with `weight`.
+    ///
+    /// The weights of parallel edges are summed.
+    pub fn from_stable_petgraph_weighted<N, E, Ix, F>(
+        g: &StableGraph<N, E, Undirected, Ix>,
+        mut weight: F,
+    ) -> (GraphBuf, Vec<NodeIndex<Ix>>)
+    where
+        Ix: IndexType,
+        F: FnMut(&E) -> Idx,
+    {
+        let nodes: Vec<_> = g.node_indices().collect();
+        let mut ids = vec![0; g.node_bound()];
+        for (v, node) in nodes.iter().enumerate() {
+            ids[node.index()] = v as Idx;
+        }
+
+        let edges = g.edge_references().map(|e| {
+            (
+                ids[e.source().index()],
+                ids[e.target().index()],
+                weight(e.weight()),
+            )
+        });
+        (GraphBuf::from_weighted_pairs(nodes.len(), edges), nodes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use crate::{GraphBuf, Mode};
+    use petgraph::graph::UnGraph;
+    use petgraph::stable_graph::StableUnGraph;
+
+    #[test]
+    fn from_petgraph() {
+        let g = UnGraph::<(), ()>::from_edges([(0, 1), (0, 4), (1, 2), (1, 4), (2, 3), (3, 4)]);
+        let mut graph = GraphBuf::from_petgraph(&g);
+
+        assert_eq!(graph.xadj(), [0, 2, 5, 7, 9, 12]);
+        assert_eq!(graph.adjncy(), [1, 4, 0, 2, 4, 1, 3, 2, 4, 0, 1, 3]);
+
+        let result = graph.as_graph().partition(2, 0.03, true, 1234, Mode::Eco);
+        assert_eq!(result.part.len(), g.node_count());
+        assert!(result.part.iter().all(|&p| p == 0 || p == 1));
+    }
+
+    #[test]
+    fn from_petgraph_weighted() {
+        let g = UnGraph::<(), u8>::from_edges([(0, 1, 2), (1, 2, 3), (2, 1, 4)]);
+        let graph = GraphBuf::from_petgraph_weighted(&g, |&w| w.into());
+
+        assert_eq!(graph.xadj(), [0, 1, 3, 4]);
+        assert_eq!(graph.adjncy(), [1, 0, 2, 1]);
+        assert_eq!(graph.adjwgt(), Some(&[2, 2, 7, 7][..]));
+    }
+
+    #[test]
+    fn from_stable_petgraph() {
+        let mut g = StableUnGraph::<(), ()>::default();
+        let a = g.add_node(());
+        let b = g.add_node(());
+        let c = g.add_node(());
+        let d = g.add_node(());
+        g.add_edge(a, b, ());
+        g.add_edge(b, d, ());
+        g.add_edge(d, a, ());
+        g.remove_node(c);
+
+        let (graph, nodes) = GraphBuf::from_stable_petgraph(&g);
+
+        assert_eq!(nodes, [a, b, d]);
+        assert_eq!(graph.xadj(), [0, 2, 4, 6]);
+        assert_eq!(graph.adjncy(), [1, 2, 0, 2, 0, 1]);
+    }
+}
@@ -0,0 +1,123 @@
+//! Local refinement of an existing partition.
+
+use crate::{block_weights, edge_cut, Graph, GraphRef, Idx};
+
+impl Graph<'_> {
+    /// Improves the partition `part` of the graph into `n_parts` blocks in
+    /// place, by moving boundary vertices to neighboring blocks. Returns the
+    /// edge cut of the refined partition.
+    ///
+    /// KaHIP's library interface has no entry point to refine a given
+    /// partition: [`Graph::partition_with`] always starts from scratch. In
+    /// adaptive simulations, where the previous partition is a good starting
+    /// point and moving vertices is costly, this pass is an alternative: it
+    /// keeps most vertices in place and never increases the edge cut.
+    ///
+    /// Each vertex is moved to the block it shares the most edge weight with,
+    /// if that is more than with its own block, and if the block weighs at
+    /// most `(1 + imbalance)` times the average block weight afterwards, as
+    /// KaHIP's constraint. These are the moves of Fiduccia-Mattheyses with a
+    /// positive gain. Moves with a negative gain are never made, so the pass
+    /// stops at a local minimum, and never fixes a partition that is already
+    /// imbalanced. Passes over the vertices are repeated until no vertex
+    /// moves. Vertex weights count unless there are several per vertex.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if:
+    /// - the length of `part` is different than the number of vertices,
+    /// - an element of `part` is not in `0..n_parts`, or
+    /// - `imbalance` is negative or NaN.
+    pub fn refine_partition(&self, part: &mut [Idx], n_parts: Idx, imbalance: f64) -> Idx {
+        let graph = GraphRef::from(self);
+        assert_eq!(part.len(), graph.num_vertices());
+        assert!(imbalance >= 0.0, "invalid imbalance {imbalance}");
+        let vwgt = graph.vwgt.filter(|_| graph.ncon == 1);
+        let vertex_weight = |v: usize| vwgt.map_or(1, |vwgt| vwgt[v] as i64);
+
+        let mut weights = block_weights(part, vwgt, n_parts);
+        let total: i64 = weights.iter().sum();
+        let average = (total as f64 / n_parts as f64).ceil();
+        let max_weight = ((1.0 + imbalance) * average).floor() as i64;
+
+        // The edge weight between the current vertex and each block.
+        let mut shared = vec![0i64; n_parts as usize];
+        let mut adjacent = Vec::new();
+        loop {
+            let mut moved = false;
+            for v in 0..part.len() {
+                for k in graph.xadj[v] as usize..graph.xadj[v + 1] as usize {
+                    let block = part[graph.adjncy[k] as usize];
+                    if shared[block as usize] == 0 {
+                        adjacent.push(block);
+                    }
+                    shared[block as usize] += graph.adjwgt.map_or(1, |adjwgt| adjwgt[k] as i64);
+                }
+
+                let own = part[v];
+                let w = vertex_weight(v);
+                let target = adjacent
+                    .iter()
+                    .copied()
+                    .filter(|&b| b != own && weights[b as usize] + w <= max_weight)
+                    .filter(|&b| shared[b as usize] > shared[own as usize])
+                    .max_by_key(|&b| (shared[b as usize], -b));
+                if let Some(target) = target {
+                    part[v] = target;
+                    weights[own as usize] -= w;
+                    weights[target as usize] += w;
+                    moved = true;
+                }
+
+                for block in adjacent.drain(..) {
+                    shared[block as usize] = 0;
+                }
+            }
+            if !moved {
+                return edge_cut(graph, part);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use crate::testgraphs::grid_2d;
+    use crate::{balance, edge_cut, Idx, PartitionConfig};
+
+    #[test]
+    fn refine_partition() {
+        // The left and right halves of a 6x6 grid, with a stray vertex of
+        // block 1 on the left.
+        let mut grid = grid_2d(6, 6);
+        let graph = grid.as_graph();
+        let halves: Vec<Idx> = (0..36).map(|v| (v % 6 >= 3) as Idx).collect();
+        let mut part = halves.clone();
+        part[7] = 1;
+        assert_eq!(edge_cut(&graph, &part), 10);
+        assert_eq!(graph.refine_partition(&mut part, 2, 0.03), 6);
+        assert_eq!(part, halves);
+
+        // Without room in block 0, the stray vertex stays where it is.
+        let mut part = halves.clone();
+        part[7] = 1;
+        part[4] = 0;
+        let stuck = part.clone();
+        assert_eq!(graph.refine_partition(&mut part, 2, 0.0), 13);
+        assert_eq!(part, stuck);
+    }
+
+    #[test]
+    fn refine_good_partition() {
+        let mut grid = grid_2d(20, 20);
+        let mut graph = grid.as_graph();
+        let cfg = PartitionConfig::new(4).set_seed(1234);
+        let mut part = graph.partition_with(&cfg).part;
+        let cut = edge_cut(&graph, &part);
+        let before = balance(&part, None, 4);
+
+        assert!(graph.refine_partition(&mut part, 4, 0.03) <= cut);
+        assert!(balance(&part, None, 4) <= before.max(1.03));
+    }
+}
@@ -0,0 +1,116 @@
+//! Conversions from [`sprs`]'s sparse matrices.
+
+use crate::{GraphBuf, GraphError, Idx};
+use sprs::CsMat;
+
+impl GraphBuf {
+    /// Creates a new [`GraphBuf`] from the sparsity pattern of a square
+    /// matrix.
+    ///
+    /// Vertices `u` and `v` are neighbors when the entry at row `u` and column
+    /// `v` is stored. Diagonal entries are dropped since they would be
+    /// self-loops, and the values of the entries are ignored.
+    ///
+    /// The matrix should be structurally symmetric, see
+    /// [`Graph::is_symmetric`](crate::Graph::is_symmetric).
+    pub fn from_csmat<T: Clone + Default>(mat: &CsMat<T>) -> Result<GraphBuf, GraphError> {
+        let mut graph = GraphBuf::from_csmat_weighted(mat, |_| 1)?;
+        graph.adjwgt = None;
+        Ok(graph)
+    }
+
+    /// Same as [`GraphBuf::from_csmat`], computing the weight of each edge
+    /// from the value of its entry with `weight`, e.g. `|&a| a.abs() as Idx`.
+    pub fn from_csmat_weighted<T, F>(mat: &CsMat<T>, mut weight: F) -> Result<GraphBuf, GraphError>
+    where
+        T: Clone + Default,
+        F: FnMut(&T) -> Idx,
+    {
+        if mat.rows() != mat.cols() {
+            return Err(GraphError::NotSquare {
+                rows: mat.rows(),
+                cols: mat.cols(),
+            });
+        }
+
+        let csr;
+        let mat = if mat.is_csr() {
+            mat.view()
+        } else {
+            csr = mat.to_other_storage();
+            csr.view()
+        };
+
+        let mut xadj = Vec::with_capacity(mat.rows() + 1);
+        let mut adjncy = Vec::with_capacity(mat.nnz());
+        let mut adjwgt = Vec::with_capacity(mat.nnz());
+
+        xadj.push(0);
+        for (u, row) in mat.outer_iterator().enumerate() {
+            for (v, value) in row.iter() {
+                if v == u {
+                    continue;
+                }
+                adjncy.push(v as Idx);
+                adjwgt.push(weight(value));
+            }
+            xadj.push(adjncy.len() as Idx);
+        }
+
+        Ok(GraphBuf::new(xadj, adjncy).set_adjwgt(adjwgt))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use crate::{GraphBuf, GraphError};
+    use sprs::{CsMat, TriMat};
+
+    /// The matrix of the README graph, with a diagonal.
+    fn matrix() -> TriMat<f64> {
+        let edges = [(0, 1), (0, 4), (1, 2), (1, 4), (2, 3), (3, 4)];
+        let mut mat = TriMat::new((5, 5));
+        for v in 0..5 {
+            mat.add_triplet(v, v, 4.0);
+        }
+        for (i, &(u, v)) in edges.iter().enumerate() {
+            mat.add_triplet(u, v, -(i as f64) - 1.0);
+            mat.add_triplet(v, u, -(i as f64) - 1.0);
+        }
+        mat
+    }
+
+    #[test]
+    fn from_csmat() {
+        let graph = GraphBuf::from_csmat(&matrix().to_csr::<usize>()).unwrap();
+
+        assert_eq!(graph.xadj(), [0, 2, 5, 7, 9, 12]);
+        assert_eq!(graph.adjncy(), [1, 4, 0, 2, 4, 1, 3, 2, 4, 0, 1, 3]);
+        assert_eq!(graph.adjwgt(), None);
+
+        let csc = GraphBuf::from_csmat(&matrix().to_csc::<usize>()).unwrap();
+        assert_eq!(csc, graph);
+    }
+
+    #[test]
+    fn from_csmat_weighted() {
+        let mat = matrix().to_csr::<usize>();
+        let graph = GraphBuf::from_csmat_weighted(&mat, |&a| -a as _).unwrap();
+
+        assert_eq!(graph.adjncy(), [1, 4, 0, 2, 4, 1, 3, 2, 4, 0, 1, 3]);
+        assert_eq!(
+            graph.adjwgt(),
+            Some(&[1, 2, 1, 3, 4, 3, 5, 5, 6, 2, 4, 6][..])
+        );
+    }
+
+    #[test]
+    fn from_csmat_not_square() {
+        let mat = CsMat::<f64>::zero((2, 3));
+        assert_eq!(
+            GraphBuf::from_csmat(&mat),
+            Err(GraphError::NotSquare { rows: 2, cols: 3 })
+        );
+    }
+}
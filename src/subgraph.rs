@@ -0,0 +1,180 @@
+//! Subgraphs of a partitioned graph.
+
+use crate::{Graph, GraphBuf, Idx, PartitionConfig, PartitionError, PartitionResult};
+
+/// Returns the subgraph induced by the vertices of `block`, along with the
+/// original id of each of its vertices.
+///
+/// Vertices keep their relative order, so vertex `i` of the subgraph is
+/// vertex `ids[i]` of `graph`. Edges between two vertices of the block are
+/// kept with their weights, edges leaving the block are dropped. Vertex
+/// weights are kept too.
+///
+/// # Panics
+///
+/// This function panics if the length of `part` is different than the number
+/// of vertices.
+pub fn induced_subgraph(graph: &Graph, part: &[Idx], block: Idx) -> (GraphBuf, Vec<usize>) {
+    assert_eq!(part.len(), graph.xadj.len() - 1);
+
+    let ids: Vec<usize> = (0..part.len()).filter(|&v| part[v] == block).collect();
+    let mut new_ids = vec![-1; part.len()];
+    for (i, &v) in ids.iter().enumerate() {
+        new_ids[v] = i as Idx;
+    }
+
+    let mut xadj = Vec::with_capacity(ids.len() + 1);
+    let mut adjncy = Vec::new();
+    let mut adjwgt = Vec::new();
+    xadj.push(0);
+    for &v in &ids {
+        for k in graph.xadj[v] as usize..graph.xadj[v + 1] as usize {
+            let u = graph.adjncy[k] as usize;
+            if part[u] != block {
+                continue;
+            }
+            adjncy.push(new_ids[u]);
+            if let Some(graph_adjwgt) = &graph.adjwgt {
+                adjwgt.push(graph_adjwgt[k]);
+            }
+        }
+        xadj.push(adjncy.len() as Idx);
+    }
+
+    let mut subgraph = GraphBuf::new(xadj, adjncy);
+    if graph.adjwgt.is_some() {
+        subgraph = subgraph.set_adjwgt(adjwgt);
+    }
+    if let Some(graph_vwgt) = &graph.vwgt {
+        let ncon = graph.ncon;
+        let vwgt = ids
+            .iter()
+            .flat_map(|&v| &graph_vwgt[v * ncon..(v + 1) * ncon])
+            .copied()
+            .collect();
+        subgraph.vwgt = Some(vwgt);
+        subgraph.ncon = ncon;
+    }
+    (subgraph, ids)
+}
+
+impl Graph<'_> {
+    /// Partitions the graph according to `cfg`, leaving out the vertices `v`
+    /// where `ignored[v]` is set.
+    ///
+    /// The ignored vertices are removed with [`induced_subgraph`], together
+    /// with their edges, and the remaining vertices are partitioned. They get
+    /// the block computed on that reduced graph, while ignored vertices get
+    /// the block -1, which is not a valid block for the other functions of
+    /// this crate. The edge cut, as well as the number of vertices `cfg` is
+    /// checked against, refer to the reduced graph. The remaining fixed
+    /// vertices, see [`Graph::set_fixed_vertices`], stay fixed.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if:
+    /// - the length of `ignored` is different than the number of vertices,
+    ///   or
+    /// - the arguments are out of range, see [`Graph::try_partition_masked`].
+    pub fn partition_masked(&mut self, ignored: &[bool], cfg: &PartitionConfig) -> PartitionResult {
+        self.try_partition_masked(ignored, cfg).unwrap()
+    }
+
+    /// Same as [`Graph::partition_masked`], returning an error if the
+    /// arguments are out of range for the reduced graph, see
+    /// [`Graph::try_partition_with`].
+    ///
+    /// # Panics
+    ///
+    /// This function panics if the length of `ignored` is different than the
+    /// number of vertices.
+    pub fn try_partition_masked(
+        &mut self,
+        ignored: &[bool],
+        cfg: &PartitionConfig,
+    ) -> Result<PartitionResult, PartitionError> {
+        assert_eq!(ignored.len(), self.xadj.len() - 1);
+
+        let kept: Vec<Idx> = ignored.iter().map(|&ignored| ignored as Idx).collect();
+        let (mut subgraph, ids) = induced_subgraph(self, &kept, 0);
+        let fixed: Option<Vec<Idx>> = self
+            .fixed
+            .map(|fixed| ids.iter().map(|&v| fixed[v]).collect());
+        let mut subgraph = subgraph.as_graph();
+        if let Some(fixed) = &fixed {
+            subgraph = subgraph.set_fixed_vertices(fixed);
+        }
+        let result = subgraph.try_partition_with(cfg)?;
+
+        let mut part = vec![-1; ignored.len()];
+        for (&v, &block) in ids.iter().zip(&result.part) {
+            part[v] = block;
+        }
+        Ok(PartitionResult { part, ..result })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use crate::testgraphs::grid_2d;
+    use crate::{edge_cut, induced_subgraph, Graph, Idx, PartitionConfig};
+
+    #[test]
+    fn induced_subgraph_of_block() {
+        let mut xadj = vec![0, 2, 5, 7, 9, 12];
+        let mut adjncy = vec![1, 4, 0, 2, 4, 1, 3, 2, 4, 0, 1, 3];
+        let mut vwgt = vec![1, 2, 3, 4, 5];
+        let mut adjwgt = vec![1, 2, 1, 3, 4, 3, 5, 5, 6, 2, 4, 6];
+        let graph = Graph::new(&mut xadj, &mut adjncy)
+            .set_vwgt(&mut vwgt)
+            .set_adjwgt(&mut adjwgt);
+        let part = [0, 0, 1, 1, 0];
+
+        // Block 0 is the triangle 0-1-4.
+        let (subgraph, ids) = induced_subgraph(&graph, &part, 0);
+        assert_eq!(ids, [0, 1, 4]);
+        assert_eq!(subgraph.xadj(), [0, 2, 4, 6]);
+        assert_eq!(subgraph.adjncy(), [1, 2, 0, 2, 0, 1]);
+        assert_eq!(subgraph.adjwgt(), Some(&[1, 2, 1, 4, 2, 4][..]));
+        assert_eq!(subgraph.vwgt(), Some(&[1, 2, 5][..]));
+
+        // Block 1 is the edge 2-3.
+        let (subgraph, ids) = induced_subgraph(&graph, &part, 1);
+        assert_eq!(ids, [2, 3]);
+        assert_eq!(subgraph.xadj(), [0, 1, 2]);
+        assert_eq!(subgraph.adjncy(), [1, 0]);
+        assert_eq!(subgraph.adjwgt(), Some(&[5, 5][..]));
+
+        // An empty block.
+        let (subgraph, ids) = induced_subgraph(&graph, &part, 2);
+        assert!(ids.is_empty());
+        assert_eq!(subgraph.xadj(), [0]);
+    }
+
+    #[test]
+    fn partition_masked() {
+        let mut grid = grid_2d(6, 6);
+        let mut graph = grid.as_graph();
+        let mut ignored = vec![false; 36];
+        ignored[0] = true;
+        ignored[20] = true;
+        let cfg = PartitionConfig::new(3).set_seed(1234);
+
+        let result = graph.partition_masked(&ignored, &cfg);
+        assert_eq!(result.part.len(), 36);
+        for (&ignored, &block) in ignored.iter().zip(&result.part) {
+            if ignored {
+                assert_eq!(block, -1);
+            } else {
+                assert!((0..3).contains(&block));
+            }
+        }
+
+        // The edge cut is the one of the reduced graph.
+        let kept: Vec<Idx> = ignored.iter().map(|&ignored| ignored as Idx).collect();
+        let (reduced, _) = induced_subgraph(&graph, &kept, 0);
+        let part: Vec<Idx> = result.part.iter().copied().filter(|&p| p != -1).collect();
+        assert_eq!(result.edge_cut, edge_cut(&reduced, &part));
+    }
+}
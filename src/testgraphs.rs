@@ -0,0 +1,144 @@
+//! Deterministic graph generators for tests and benchmarks.
+
+use crate::{GraphBuf, Idx};
+
+/// Returns the `nx` by `ny` grid graph.
+///
+/// Vertex `j * nx + i` is at column `i` and row `j`, and is linked to the
+/// vertices above, on the left, on the right and below it, in this order.
+pub fn grid_2d(nx: usize, ny: usize) -> GraphBuf {
+    let (nx, ny) = (nx as Idx, ny as Idx);
+    let mut xadj = vec![0];
+    let mut adjncy = Vec::new();
+    for j in 0..ny {
+        for i in 0..nx {
+            let v = j * nx + i;
+            if j > 0 {
+                adjncy.push(v - nx);
+            }
+            if i > 0 {
+                adjncy.push(v - 1);
+            }
+            if i < nx - 1 {
+                adjncy.push(v + 1);
+            }
+            if j < ny - 1 {
+                adjncy.push(v + nx);
+            }
+            xadj.push(adjncy.len() as Idx);
+        }
+    }
+    GraphBuf::new(xadj, adjncy)
+}
+
+/// Returns a random graph with `n` vertices, each of them having `degree`
+/// neighbors.
+///
+/// The graph is drawn by pairing `degree` stubs per vertex at random, while
+/// avoiding self-loops and parallel edges. The same arguments always give
+/// the same graph, on every platform.
+///
+/// # Panics
+///
+/// This function panics if `degree` is not smaller than `n`, or if
+/// `n * degree` is odd, since no such graph exists then.
+pub fn random_regular(n: usize, degree: usize, seed: u64) -> GraphBuf {
+    assert!(n == 0 || degree < n, "degree must be smaller than n");
+    assert!((n * degree).is_multiple_of(2), "n * degree must be even");
+
+    let mut rng = SplitMix64(seed);
+    'attempt: loop {
+        let mut stubs: Vec<Idx> = (0..n * degree).map(|s| (s / degree) as Idx).collect();
+        let mut neighbors = vec![Vec::with_capacity(degree); n];
+        let mut edges = Vec::with_capacity(n * degree / 2);
+        while !stubs.is_empty() {
+            // Give up on this pairing if no suitable pair shows up.
+            let mut tries = 0;
+            let (i, j) = loop {
+                let i = rng.below(stubs.len());
+                let j = rng.below(stubs.len());
+                let (u, v) = (stubs[i], stubs[j]);
+                if u != v && !neighbors[u as usize].contains(&v) {
+                    break (i, j);
+                }
+                tries += 1;
+                if tries > 100 * stubs.len() {
+                    continue 'attempt;
+                }
+            };
+            let (u, v) = (stubs[i], stubs[j]);
+            neighbors[u as usize].push(v);
+            neighbors[v as usize].push(u);
+            edges.push((u, v));
+            stubs.swap_remove(i.max(j));
+            stubs.swap_remove(i.min(j));
+        }
+        return GraphBuf::from_edge_list(n, &edges);
+    }
+}
+
+/// The SplitMix64 random number generator, which is enough for tests and
+/// keeps this crate free of a `rand` dependency.
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// Returns a number in `0..n`.
+    fn below(&mut self, n: usize) -> usize {
+        (self.next() % n as u64) as usize
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::{grid_2d, random_regular};
+    use crate::Mode;
+
+    #[test]
+    fn grid() {
+        let mut graph = grid_2d(4, 3);
+        let graph = graph.as_graph();
+
+        assert_eq!(graph.num_vertices(), 12);
+        assert_eq!(graph.num_edges(), 17);
+        assert!(graph.is_symmetric());
+        assert!(!graph.has_self_loops());
+        assert_eq!(graph.neighbors(0), [1, 4]);
+        assert_eq!(graph.neighbors(5), [1, 4, 6, 9]);
+    }
+
+    #[test]
+    fn grid_bisection() {
+        let mut graph = grid_2d(10, 10);
+        let result = graph.as_graph().partition(2, 0.03, true, 0, Mode::Eco);
+
+        // The best bisection cuts 10 edges.
+        assert!(result.edge_cut <= 20);
+    }
+
+    #[test]
+    fn random_regular_graphs() {
+        for (n, degree) in [(10, 3), (50, 4), (7, 6), (1, 0)] {
+            let mut graph = random_regular(n, degree, 42);
+            let graph = graph.as_graph();
+
+            assert_eq!(graph.num_vertices(), n);
+            assert!(graph.is_symmetric());
+            assert!(!graph.has_self_loops());
+            for v in 0..n {
+                assert_eq!(graph.degree(v), degree);
+            }
+        }
+
+        assert_eq!(random_regular(20, 3, 1), random_regular(20, 3, 1));
+        assert_ne!(random_regular(20, 3, 1), random_regular(20, 3, 2));
+    }
+}
@@ -0,0 +1,63 @@
+//! Partitions of graphs given with `usize` indices.
+
+use crate::{checked_idx, Graph, Idx, PartitionConfig};
+
+/// Partitions the graph given by `xadj` and `adjncy`, with `usize` indices.
+///
+/// This is the same as [`Graph::new_ref`] followed by
+/// [`Graph::partition_with`], for code that stores its graphs with `usize`
+/// indices and never deals with KaHIP's [`Idx`]. Returns the block of each
+/// vertex.
+///
+/// KaHIP only works on [`Idx`] arrays, so `xadj` and `adjncy` are copied and
+/// converted, and so is the result: this costs two allocations and a pass
+/// over the graph on top of the partition itself. Build a [`Graph`] directly
+/// to partition the same graph several times.
+///
+/// # Panics
+///
+/// This function panics if:
+/// - an element of `xadj` or `adjncy` does not fit in an [`Idx`],
+/// - the arrays are inconsistent, see [`Graph::new`], or
+/// - the arguments are out of range, see [`Graph::try_partition_with`].
+pub fn upartition(xadj: &[usize], adjncy: &[usize], cfg: &PartitionConfig) -> Vec<usize> {
+    let convert = |values: &[usize]| -> Vec<Idx> {
+        values.iter().map(|&i| checked_idx(i).unwrap()).collect()
+    };
+    let xadj = convert(xadj);
+    let adjncy = convert(adjncy);
+    let result = Graph::new_ref(&xadj, &adjncy).partition_with(cfg);
+    result
+        .part
+        .into_iter()
+        .map(|block| block as usize)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+
+    use crate::{upartition, Graph, PartitionConfig};
+
+    #[test]
+    fn same_as_idx() {
+        let xadj = [0, 2, 5, 7, 9, 12];
+        let adjncy = [1, 4, 0, 2, 4, 1, 3, 2, 4, 0, 1, 3];
+        let cfg = PartitionConfig::new(2).set_seed(1234);
+
+        let part = upartition(&xadj, &adjncy, &cfg);
+
+        let xadj: Vec<_> = xadj.iter().map(|&i| i as _).collect();
+        let adjncy: Vec<_> = adjncy.iter().map(|&i| i as _).collect();
+        let expected = Graph::new_ref(&xadj, &adjncy).partition_with(&cfg).part;
+        let expected: Vec<usize> = expected.iter().map(|&block| block as usize).collect();
+        assert_eq!(part, expected);
+    }
+
+    #[test]
+    #[should_panic]
+    fn index_overflow() {
+        let xadj = [0, usize::MAX];
+        let _ = upartition(&xadj, &[], &PartitionConfig::new(1));
+    }
+}
@@ -0,0 +1,94 @@
+//! Property-based tests of the CSR constructors.
+
+#![cfg(feature = "std")]
+
+use kahip::{GraphBuf, Idx, WeightMerge};
+use proptest::collection::vec;
+use proptest::prelude::*;
+
+/// A number of vertices and a list of edges between them.
+fn edge_list() -> impl Strategy<Value = (usize, Vec<(Idx, Idx)>)> {
+    (1usize..30).prop_flat_map(|n| {
+        let vertex = 0..n as Idx;
+        (Just(n), vec((vertex.clone(), vertex), 0..100))
+    })
+}
+
+/// Checks the invariants of a CSR ready for KaHIP.
+fn assert_normalized(graph: &mut GraphBuf, n_vertices: usize) {
+    assert_eq!(graph.xadj().len(), n_vertices + 1);
+    assert_eq!(*graph.xadj().last().unwrap() as usize, graph.adjncy().len());
+    if let Some(adjwgt) = graph.adjwgt() {
+        assert_eq!(adjwgt.len(), graph.adjncy().len());
+    }
+
+    let graph = graph.as_graph();
+    assert_eq!(graph.validate(), Ok(()));
+    assert!(graph.is_symmetric());
+    assert!(!graph.has_self_loops());
+    for v in 0..n_vertices {
+        // Sorted and without duplicates.
+        assert!(graph.neighbors(v).windows(2).all(|w| w[0] < w[1]));
+    }
+}
+
+proptest! {
+    #[test]
+    fn from_edge_list((n, edges) in edge_list()) {
+        let mut graph = GraphBuf::from_edge_list(n, &edges);
+        assert_normalized(&mut graph, n);
+        prop_assert_eq!(graph.adjwgt(), None);
+    }
+
+    #[test]
+    fn from_weighted_edge_list((n, edges) in edge_list()) {
+        let weights: Vec<Idx> = (1..=edges.len() as Idx).collect();
+        let mut graph = GraphBuf::from_weighted_edge_list(n, &edges, &weights);
+        assert_normalized(&mut graph, n);
+
+        // Weights are merged, not lost, except those of self-loops.
+        let total: Idx = edges
+            .iter()
+            .zip(&weights)
+            .filter(|((u, v), _)| u != v)
+            .map(|(_, w)| 2 * w)
+            .sum();
+        prop_assert_eq!(graph.adjwgt().unwrap().iter().sum::<Idx>(), total);
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    fn from_edges((n, edges) in edge_list()) {
+        let mut graph = GraphBuf::from_edges(n, &edges);
+        prop_assert_eq!(graph.adjncy().len(), 2 * edges.len());
+        let graph = graph.as_graph();
+        prop_assert_eq!(graph.validate(), Ok(()));
+        prop_assert!(graph.is_symmetric());
+    }
+
+    #[test]
+    fn symmetrize((n, arcs) in edge_list()) {
+        // Read the edges as arcs of a directed graph.
+        let mut xadj = vec![0; n + 1];
+        for &(u, _) in &arcs {
+            xadj[u as usize + 1] += 1;
+        }
+        for v in 0..n {
+            xadj[v + 1] += xadj[v];
+        }
+        let mut adjncy = vec![0; arcs.len()];
+        let mut next = xadj.clone();
+        for &(u, v) in &arcs {
+            adjncy[next[u as usize] as usize] = v;
+            next[u as usize] += 1;
+        }
+        let directed = GraphBuf::new(xadj, adjncy);
+
+        for merge in [WeightMerge::Sum, WeightMerge::Max] {
+            let mut graph = directed.symmetrize(merge);
+            assert_normalized(&mut graph, n);
+            let undirected = GraphBuf::from_edge_list(n, &arcs);
+            prop_assert_eq!(graph.adjncy(), undirected.adjncy());
+        }
+    }
+}
@@ -0,0 +1,27 @@
+//! Checks the metrics of the crate built without `std`, with
+//! `cargo test --no-default-features --test no_std_metrics`.
+
+#![no_std]
+
+use kahip::{csr, GraphRef};
+
+#[test]
+fn metrics_without_std() {
+    let xadj = [0, 2, 5, 7, 9, 12];
+    let adjncy = [1, 4, 0, 2, 4, 1, 3, 2, 4, 0, 1, 3];
+    let adjwgt = [1, 2, 1, 3, 4, 3, 5, 5, 6, 2, 4, 6];
+    let part = [0, 0, 1, 1, 0];
+
+    assert_eq!(csr::edge_cut(&xadj, &adjncy, None, &part), 2);
+    assert_eq!(csr::edge_cut(&xadj, &adjncy, Some(&adjwgt), &part), 9);
+    assert_eq!(csr::communication_volume(&xadj, &adjncy, &part), 4);
+    assert_eq!(csr::balance(&part, None, 2), 1.2);
+    assert_eq!(csr::achieved_imbalance(&[0, 1, 1, 0], None, 2), 0.0);
+    assert!(csr::is_balanced(&[0, 1, 1, 0], None, 2, 0.03));
+    assert_eq!(csr::block_sizes(&part, 2), [3, 2]);
+
+    let graph = GraphRef::new(&xadj, &adjncy).set_adjwgt(&adjwgt);
+    assert_eq!(kahip::edge_cut(graph, &part), 9);
+    assert_eq!(kahip::per_block_cut(graph, &part, 2), [9, 9]);
+    assert_eq!(kahip::boundary_vertices(graph, &part), [1, 2, 3, 4]);
+}
@@ -0,0 +1,33 @@
+//! Run with `mpirun -n 2 cargo test --features mpi --test parhip -- --ignored`.
+
+#![cfg(feature = "mpi")]
+
+use kahip::{DistGraph, ParMode};
+use mpi::traits::Communicator;
+
+#[test]
+#[ignore = "needs to run under mpirun"]
+fn partition_distributed_ring() {
+    let universe = mpi::initialize().unwrap();
+    let world = universe.world();
+    let size = world.size() as u64;
+    let rank = world.rank() as u64;
+
+    // A ring of 10 vertices per process.
+    let n = 10 * size;
+    let vtxdist: Vec<u64> = (0..=size).map(|r| 10 * r).collect();
+    let mut xadj = vec![0];
+    let mut adjncy = Vec::new();
+    for v in vtxdist[rank as usize]..vtxdist[rank as usize + 1] {
+        adjncy.push((v + n - 1) % n);
+        adjncy.push((v + 1) % n);
+        xadj.push(adjncy.len() as u64);
+    }
+
+    let graph = DistGraph::new(&vtxdist, &xadj, &adjncy);
+    let (part, edge_cut) = graph.partition(&world, 2, 0.03, true, 0, ParMode::FastMesh);
+
+    assert_eq!(part.len(), 10);
+    assert!(part.iter().all(|&p| p < 2));
+    assert!(edge_cut >= 2);
+}